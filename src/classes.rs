@@ -0,0 +1,78 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains precomputed byte classes for fast membership tests.
+use std::ops::RangeInclusive;
+
+/// A set of bytes stored as a 256 bit membership table.
+///
+/// Build it once and reuse it with the `satisfy_class!` matcher. Checking
+/// membership is a single table lookup so it should outperform a chain of
+/// `||` comparisons for anything but the smallest classes.
+///
+/// ```
+/// use abortable_parser::classes::ByteClass;
+/// let ident = ByteClass::from_ranges(&[b'a'..=b'z', b'A'..=b'Z']).with_bytes(b"_");
+/// assert!(ident.matches(b'_'));
+/// assert!(!ident.matches(b'1'));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteClass {
+    table: [u64; 4],
+}
+
+impl ByteClass {
+    /// Constructs an empty ByteClass.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a ByteClass containing each of the bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::new().with_bytes(bytes)
+    }
+
+    /// Constructs a ByteClass containing every byte in each of the ranges.
+    pub fn from_ranges(ranges: &[RangeInclusive<u8>]) -> Self {
+        Self::new().with_ranges(ranges)
+    }
+
+    /// Adds each of the bytes to the ByteClass.
+    pub fn with_bytes(mut self, bytes: &[u8]) -> Self {
+        for b in bytes {
+            self.insert(*b);
+        }
+        self
+    }
+
+    /// Adds every byte in each of the ranges to the ByteClass.
+    pub fn with_ranges(mut self, ranges: &[RangeInclusive<u8>]) -> Self {
+        for r in ranges {
+            for b in r.clone() {
+                self.insert(b);
+            }
+        }
+        self
+    }
+
+    /// Adds a single byte to the ByteClass.
+    pub fn insert(&mut self, b: u8) {
+        self.table[(b >> 6) as usize] |= 1 << (b & 63);
+    }
+
+    /// Returns true if the byte is a member of the ByteClass.
+    pub fn matches(&self, b: u8) -> bool {
+        self.table[(b >> 6) as usize] & (1 << (b & 63)) != 0
+    }
+}
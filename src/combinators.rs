@@ -13,7 +13,8 @@
 //  limitations under the License.
 
 //! Contains combinators that can assemble other matchers or combinators into more complex grammars.
-use super::{Error, InputIter, Result};
+use super::iter::StrIter;
+use super::{Error, InputIter, Offsetable, Peekable, Result, Span, SpanRange};
 
 /// Turns a `Result` to it's inverse.
 ///
@@ -112,14 +113,367 @@ macro_rules! peek {
     };
 }
 
+/// Checks whether the given matcher would match without consuming the input.
+///
+/// Returns `true` if the matcher completes and `false` if it fails. Aborts and
+/// Incompletes are passed through.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// # let iter: iter::SliceIter<u8> = "foo".into();
+/// let tok = peek_is!(iter, text_token!("bar"));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 0);
+///     assert!(!o);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! peek_is {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Result;
+        let _i = $i.clone();
+        match $f!(_i, $($args)*) {
+            Result::Complete(_, _) => Result::Complete($i, true),
+            Result::Fail(_) => Result::Complete($i, false),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::peek_is!($i, run!($f))
+    }};
+}
+
+/// Returns the next item of the input without consuming it.
+///
+/// The output is `None` at the end of the input. This never fails so it is
+/// handy for dispatching on the first item of a token.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// # let iter: iter::SliceIter<u8> = "foo".into();
+/// let tok = peek_next!(iter);
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 0);
+///     assert_eq!(o, Some(&b'f'));
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! peek_next {
+    ($i:expr) => {{
+        use $crate::{Peekable, Result};
+        let item = $i.peek_next();
+        Result::Complete($i, item)
+    }};
+}
+
+/// Runs the given matcher as a probe and always returns the original input.
+///
+/// Returns `Some(output)` if the matcher completes and `None` if it fails.
+/// Aborts and Incompletes are passed through.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// # let iter: iter::SliceIter<u8> = "foo".into();
+/// let tok = rewind!(iter, text_token!("foo"));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 0);
+///     assert_eq!(o, Some("foo"));
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! rewind {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Result;
+        let _i = $i.clone();
+        match $f!(_i, $($args)*) {
+            Result::Complete(_, o) => Result::Complete($i, Some(o)),
+            Result::Fail(_) => Result::Complete($i, None),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::rewind!($i, run!($f))
+    }};
+}
+
+/// Matches the rule and then checks that the forbidden rule does not match
+/// right after it without consuming any more input.
+///
+/// Returns the output of the rule on success. Fails with the starting input if
+/// the forbidden rule matches.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// # let iter: iter::SliceIter<u8> = "foobaz".into();
+/// let tok = not_followed_by!(iter, text_token!("foo"), text_token!("bar"));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 3);
+///     assert_eq!(o, "foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! not_followed_by {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $g:ident!( $( $g_args:tt )* ) ) => {{
+        use $crate::{Error, Result};
+        let _i = $i.clone();
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => match $g!(i.clone(), $($g_args)*) {
+                Result::Complete(_, _) => Result::Fail(Error::new(
+                    "Matched a forbidden follow on input",
+                    Box::new(_i.clone()),
+                )),
+                Result::Fail(_) => Result::Complete(i, o),
+                Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                Result::Abort(e) => Result::Abort(e),
+            },
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident!( $( $args:tt )* ), $g:ident ) => {{
+        use $crate::run;
+        $crate::not_followed_by!($i, $f!($($args)*), run!($g))
+    }};
+
+    ($i:expr, $f:ident, $g:ident!( $( $g_args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::not_followed_by!($i, run!($f), $g!($($g_args)*))
+    }};
+
+    ($i:expr, $f:ident, $g:ident ) => {{
+        use $crate::run;
+        $crate::not_followed_by!($i, run!($f), run!($g))
+    }};
+}
+
+/// Matches the rule and then checks that the required rule matches right after
+/// it without consuming any more input.
+///
+/// Returns the output of the rule positioned after it on success. Fails with
+/// the starting input if the required rule does not match.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::ascii_digit;
+/// # fn main() {
+/// # let iter: iter::SliceIter<u8> = "5px".into();
+/// let tok = followed_by!(iter, ascii_digit, text_token!("px"));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 1);
+///     assert_eq!(o, b'5');
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! followed_by {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $g:ident!( $( $g_args:tt )* ) ) => {{
+        use $crate::{Error, Result};
+        let _i = $i.clone();
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => match $g!(i.clone(), $($g_args)*) {
+                Result::Complete(_, _) => Result::Complete(i, o),
+                Result::Fail(e) => Result::Fail(Error::caused_by(
+                    "Missing a required follow on input",
+                    Box::new(e),
+                    Box::new(_i.clone()),
+                )),
+                Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                Result::Abort(e) => Result::Abort(e),
+            },
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident!( $( $args:tt )* ), $g:ident ) => {{
+        use $crate::run;
+        $crate::followed_by!($i, $f!($($args)*), run!($g))
+    }};
+
+    ($i:expr, $f:ident, $g:ident!( $( $g_args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::followed_by!($i, run!($f), $g!($($g_args)*))
+    }};
+
+    ($i:expr, $f:ident, $g:ident ) => {{
+        use $crate::run;
+        $crate::followed_by!($i, run!($f), run!($g))
+    }};
+}
+
 /// Converts a function indentifier into a macro call. Useful when writing your own macro combinator.
+///
+/// Functions that take extra arguments after the input can be called with `run!(i, f(args))`.
 #[macro_export]
 macro_rules! run {
+    ($i:expr, $f:ident( $( $args:tt )* ) ) => {
+        $f($i, $($args)*)
+    };
+
     ($i:expr, $f:ident) => {
         $f($i)
     };
 }
 
+/// Calls a parser function by path.
+///
+/// Unlike `run!` this accepts a full path so you can reference functions in
+/// other modules. The function is resolved like any other item so it can be
+/// used for forward references and recursion between rules.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = StrIter::new(" ");
+/// let result = call_ref!(iter, abortable_parser::combinators::ascii_ws);
+/// # assert!(result.is_complete());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! call_ref {
+    ($i:expr, $fn_path:path) => {
+        $fn_path($i)
+    };
+}
+
+/// Caches the result of a rule by the offset it started at using a
+/// `PackratContext`.
+///
+/// The rule only runs the first time it is tried at an offset. Later tries at
+/// the same offset return a copy of the cached result. This turns repeated
+/// backtracking over the same rule into a lookup.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::ascii_digit;
+/// # use abortable_parser::packrat::PackratContext;
+/// # fn main() {
+/// let cache = PackratContext::new();
+/// let result = either!(StrIter::new("1b"),
+///     do_each!(_ => memoize!(cache, ascii_digit), _ => text_token!("a"), ("a")),
+///     do_each!(_ => memoize!(cache, ascii_digit), _ => text_token!("b"), ("b"))
+/// );
+/// # assert!(result.is_complete());
+/// assert_eq!(cache.len(), 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! memoize {
+    ($i:expr, $ctx:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Offsetable;
+        let offset = $i.get_offset();
+        match $ctx.get(offset) {
+            Some(result) => result,
+            None => {
+                let result = $f!($i, $($args)*);
+                $ctx.insert(offset, result.clone());
+                result
+            }
+        }
+    }};
+
+    ($i:expr, $ctx:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::memoize!($i, $ctx, run!($f))
+    }};
+}
+
+/// Runs a `Parser` value by reference.
+///
+/// The other macros only accept parser functions by name. This accepts any
+/// expression implementing `Parser` such as a closure or a `BoxedParser` held
+/// in a variable or struct field. Functions generated by `make_fn!` are items
+/// so they can already call themselves recursively through any combinator.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::Result;
+/// # use abortable_parser::combinators::ascii_digit;
+/// # use abortable_parser::parser::Parser;
+/// # fn main() {
+/// let digit = ascii_digit.boxed();
+/// let result = do_each!(StrIter::new("12"),
+///     first => ref_parser!(digit),
+///     second => ref_parser!(digit),
+///     ((first, second))
+/// );
+/// # assert!(result.is_complete());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ref_parser {
+    ($i:expr, $parser:expr) => {
+        $crate::parser::Parser::parse(&$parser, $i)
+    };
+}
+
+/// Traces a sub parser by recording its label, starting offset, and resulting
+/// `Result` variant before returning the result unchanged.
+///
+/// Traces are only recorded when the `trace` cargo feature is enabled.
+/// Otherwise this is the same as just running the sub parser.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = trace_parser!(iter, "foo token", text_token!("foo"));
+/// # assert!(result.is_complete());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! trace_parser {
+    ($i:expr, $label:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Offsetable;
+        let offset = $i.get_offset();
+        let result = $f!($i, $($args)*);
+        $crate::trace::trace_result($label, offset, &result);
+        result
+    }};
+
+    ($i:expr, $label:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::trace_parser!($i, $label, run!($f))
+    }};
+}
+
 /// Maps a `Result::Fail` to a `Result::Abort`.
 ///
 /// It leaves the rest of the Result variants untouched.
@@ -169,6 +523,81 @@ macro_rules! must {
     }};
 }
 
+/// An alias for `must!` for those coming from nom.
+///
+/// Turns `Result::Fail` into `Result::Abort` so that combinators like `either!`
+/// stop trying other branches once a `cut!` branch fails.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("let 1");
+/// let result = either!(iter,
+///     do_each!(
+///         _ => text_token!("let "),
+///         _ => cut!(text_token!("x")),
+///         (())
+///     ),
+///     discard!(text_token!("let"))
+/// );
+/// assert!(result.is_abort());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cut {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {
+        $crate::must!($i, $f!($($args)*))
+    };
+
+    ($i:expr, $f:ident( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::must!($i, run!($f($($args)*)))
+    }};
+
+    ($i:expr, $f:ident) => {
+        $crate::must!($i, $f)
+    };
+}
+
+/// Turns `Result::Fail` into `Result::Abort` with a custom message. The original
+/// error is kept as the cause.
+///
+/// Aborts from the subparser are left untouched.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = must_msg!(iter, text_token!("bar"), "Expected a bar statement");
+/// # assert!(result.is_abort());
+/// if let Result::Abort(e) = result {
+///     assert_eq!(e.get_msg(), "Expected a bar statement");
+///     assert_eq!(e.get_cause().unwrap().get_msg(), "Expected bar but didn't get it.");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! must_msg {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $msg:expr) => {{
+        let _i = $i.clone();
+        match $f!($i, $($args)*) {
+            $crate::Result::Complete(i, o) => $crate::Result::Complete(i, o),
+            $crate::Result::Incomplete(ctx) => $crate::Result::Incomplete(ctx),
+            $crate::Result::Fail(e) => $crate::Result::Abort($crate::Error::caused_by($msg, Box::new(e), Box::new(_i.clone()))),
+            $crate::Result::Abort(e) => $crate::Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident, $msg:expr) => {{
+        use $crate::run;
+        $crate::must_msg!($i, run!($f), $msg)
+    }};
+}
+
 #[macro_export]
 /// Replaces the the sub error in a Fail case with one of your own errors.
 macro_rules! with_err {
@@ -177,14 +606,14 @@ macro_rules! with_err {
         match $f!($i, $($args)*) {
             $crate::Result::Complete(i, o) => $crate::Result::Complete(i, o),
             $crate::Result::Incomplete(ctx) => $crate::Result::Incomplete(ctx),
-            $crate::Result::Fail(e) => $crate::Result::Fail($crate::Error::new($e, Box::new(_i.clone()))),
-            $crate::Result::Abort(e) => $crate::Result::Abort($crate::Error::new($e, Box::new(_i.clone()))),
+            $crate::Result::Fail(_) => $crate::Result::Fail($crate::Error::new($e, Box::new(_i.clone()))),
+            $crate::Result::Abort(_) => $crate::Result::Abort($crate::Error::new($e, Box::new(_i.clone()))),
         }
     }};
 
     ($i:expr, $f:ident( $( $args:tt )* ), $e:expr ) => {{
         use $crate::run;
-        $crate::with_err!($i, run!($f($($args)*)), $e:expr)
+        $crate::with_err!($i, run!($f($($args)*)), $e)
     }};
 
     ($i:expr, $f:ident, $e:expr) => {{
@@ -209,7 +638,7 @@ macro_rules! wrap_err {
 
     ($i:expr, $f:ident( $( $args:tt )* ), $e:expr ) => {{
         use $crate::run;
-        $crate::wrap_err!($i, run!($f($($args)*)), $e:expr)
+        $crate::wrap_err!($i, run!($f($($args)*)), $e)
     }};
 
     ($i:expr, $f:ident, $e:expr) => {{
@@ -218,7 +647,81 @@ macro_rules! wrap_err {
     }};
 }
 
-/// Traps a `Result::Abort` and converts it into a `Result::Fail`.
+/// Like `wrap_err!` but builds the message by calling a closure with the input
+/// from before the subparser ran. The closure is only called when wrapping a
+/// Fail or an Abort.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Offsetable, Result};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = wrap_err_with!(iter, text_token!("bar"), |i: &iter::StrIter| {
+///     format!("Expected bar at offset {}", i.get_offset())
+/// });
+/// if let Result::Fail(e) = result {
+///     assert_eq!(e.get_msg(), "Expected bar at offset 0");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! wrap_err_with {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $msg_fn:expr) => {{
+        let _i = $i.clone();
+        match $f!($i, $($args)*) {
+            $crate::Result::Complete(i, o) => $crate::Result::Complete(i, o),
+            $crate::Result::Incomplete(ctx) => $crate::Result::Incomplete(ctx),
+            $crate::Result::Fail(e) => $crate::Result::Fail($crate::Error::caused_by(($msg_fn)(&_i), Box::new(e), Box::new(_i.clone()))),
+            $crate::Result::Abort(e) => $crate::Result::Abort($crate::Error::caused_by(($msg_fn)(&_i), Box::new(e), Box::new(_i.clone()))),
+        }
+    }};
+
+    ($i:expr, $f:ident( $( $args:tt )* ), $msg_fn:expr ) => {{
+        use $crate::run;
+        $crate::wrap_err_with!($i, run!($f($($args)*)), $msg_fn)
+    }};
+
+    ($i:expr, $f:ident, $msg_fn:expr) => {{
+        use $crate::run;
+        $crate::wrap_err_with!($i, run!($f), $msg_fn)
+    }};
+}
+
+/// Applies a function to the error of a `Result::Abort` leaving the rest of the
+/// Result variants untouched.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Error, Result};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = map_abort!(iter, must!(text_token!("bar")), |e| {
+///     Error::caused_by("Did you mean bar?", Box::new(e), Box::new(iter.clone()))
+/// });
+/// # assert!(result.is_abort());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! map_abort {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $map:expr) => {{
+        use $crate::Result;
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => Result::Complete(i, o),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(($map)(e)),
+        }
+    }};
+
+    ($i:expr, $f:ident, $map:expr) => {{
+        use $crate::run;
+        $crate::map_abort!($i, run!($f), $map)
+    }};
+}
+
+/// Traps a `Result::Abort` and converts it into a `Result::Fail`.
 ///
 /// This is the semantic inverse of `must`.
 ///
@@ -261,6 +764,74 @@ macro_rules! trap {
     }};
 }
 
+/// Recovers from a `Result::Fail` or `Result::Abort` by skipping the input past
+/// the next match of a sync rule and returning a placeholder.
+///
+/// This lets a parser keep going after an error instead of stopping at the
+/// first one. The original error is returned if the input ends before the sync
+/// rule matches.
+///
+/// An optional `ParseSession` can be passed as the last argument to record the
+/// errors that were recovered from.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::ascii_digit;
+/// # fn main() {
+/// let iter = iter::StrIter::new("x;1");
+/// let result = recover!(iter, ascii_digit, text_token!(";"), b'0');
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(o, b'0');
+///     assert_eq!(i.get_offset(), 2);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! recover {
+    ($i:expr, $rule:ident!( $( $rule_args:tt )* ), $sync:ident!( $( $sync_args:tt )* ), $placeholder:expr $( , $session:expr )?) => {{
+        use $crate::Result;
+        (|| {
+            let err = match $rule!($i.clone(), $($rule_args)*) {
+                Result::Complete(i, o) => return Result::Complete(i, o),
+                Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                err => err,
+            };
+            let mut _i = $i.clone();
+            loop {
+                if let Result::Complete(i, _) = $sync!(_i.clone(), $($sync_args)*) {
+                    $(
+                        if let Result::Fail(e) | Result::Abort(e) = err {
+                            $session.record(e);
+                        }
+                    )?
+                    return Result::Complete(i, $placeholder);
+                }
+                if let None = _i.next() {
+                    return err;
+                }
+            }
+        })()
+    }};
+
+    ($i:expr, $rule:ident, $sync:ident, $placeholder:expr $( , $session:expr )?) => {{
+        use $crate::run;
+        $crate::recover!($i, run!($rule), run!($sync), $placeholder $( , $session )?)
+    }};
+
+    ($i:expr, $rule:ident!( $( $args:tt )* ), $sync:ident, $placeholder:expr $( , $session:expr )?) => {{
+        use $crate::run;
+        $crate::recover!($i, $rule!($($args)*), run!($sync), $placeholder $( , $session )?)
+    }};
+
+    ($i:expr, $rule:ident, $sync:ident!( $( $args:tt )* ), $placeholder:expr $( , $session:expr )?) => {{
+        use $crate::run;
+        $crate::recover!($i, run!($rule), $sync!($($args)*), $placeholder $( , $session )?)
+    }};
+}
+
 /// Turns `Result::Fail` or `Result::Incomplete` into `Result::Abort`.
 ///
 /// You must specify the error message to use in case the matcher is incomplete.
@@ -292,17 +863,94 @@ where
     }
 }
 
+/// Turns a `Result::Complete` with a vector of matched bytes into a `String`.
+///
+/// Returns `Result::Abort` with the provided message if the bytes are not valid UTF-8.
+pub fn must_string<I, S>(matched: Result<I, Vec<&u8>>, msg: S) -> Result<I, String>
+where
+    I: InputIter,
+    S: Into<String>,
+{
+    match matched {
+        Result::Complete(i, o) => match String::from_utf8(o.into_iter().cloned().collect()) {
+            Ok(s) => Result::Complete(i, s),
+            Err(_) => Result::Abort(Error::new(msg.into(), Box::new(i.clone()))),
+        },
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
 /// Turns  `Result::Incomplete` into `Result::Fail`.
+///
+/// Accepts a macro call, a function with extra arguments, or a bare function.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = complete!(iter, "No semicolon", until!(text_token!(";")));
+/// # assert!(result.is_fail());
+/// # }
+/// ```
 #[macro_export]
 macro_rules! complete {
     ($i:expr, $e:expr, $f:ident!( $( $args:tt )* ) ) => {
         $crate::combinators::complete($f!($i, $($args)*), $e)
     };
 
-    ($i:expr, $efn:expr, $f:ident) => {
-        use $crate::run
+    ($i:expr, $e:expr, $f:ident( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::complete!($i, $e, run!($f($($args)*)))
+    }};
+
+    ($i:expr, $efn:expr, $f:ident) => {{
+        use $crate::run;
         $crate::complete!($i, $efn, run!($f))
-    };
+    }};
+}
+
+/// Converts the `Vec<u8>` or `Vec<&u8>` output of a sub parser into a `String`.
+///
+/// Aborts if the collected bytes are not valid UTF-8.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # fn main() {
+/// let iter = iter::SliceIter::new("foo".as_bytes());
+/// let result = bytes_to_string!(iter, repeat!(ascii_alpha));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, "foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bytes_to_string {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use std::borrow::Borrow;
+        use $crate::Result;
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => {
+                let bytes = o.iter().map(|b| Borrow::<u8>::borrow(b)).collect();
+                $crate::combinators::must_string(Result::Complete(i, bytes), "Invalid UTF-8")
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::bytes_to_string!($i, run!($f))
+    }};
 }
 
 /// Turns `Result::Fail` and `Result::Incomplete` into `Result::Abort`.
@@ -434,6 +1082,85 @@ macro_rules! do_each {
     };
 }
 
+/// Captures a sequence of named sub parsers outputs.
+///
+/// The output is a tuple of the captured fields in the order they were declared.
+/// This is sugar for a `do_each!` that returns every binding.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::SliceIter::new("foobar".as_bytes());
+/// let result = captures!(iter, {
+///     foo: text_token!("foo"),
+///     bar: text_token!("bar"),
+/// });
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, (foo, bar)) = result {
+///     assert_eq!("foo", foo);
+///     assert_eq!("bar", bar);
+/// }
+/// # }
+/// ```
+///
+/// A single field is returned as is rather than as a one element tuple.
+#[macro_export]
+macro_rules! captures {
+    ($i:expr, { $( $field:ident : $rule:ident $( !( $( $args:tt )* ) )? ),+ $(,)? }) => {
+        $crate::do_each!($i,
+            $( $field => $rule $( !( $( $args )* ) )?, )+
+            ( $( $field ),+ )
+        )
+    };
+}
+
+/// Runs each sub parser in sequence and returns a tuple of all of their outputs.
+///
+/// This is shorthand for a `do_each!` where every output is kept. Supports up
+/// to 12 sub parsers.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::SliceIter::new("foobar".as_bytes());
+/// let result = tuple!(iter, text_token!("foo"), text_token!("bar"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, ("foo", "bar"));
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! tuple {
+    (__pair $i:expr, [ $( $done:tt )* ], [ $name:ident $( $names:ident )* ], $f:ident!( $( $args:tt )* ) $( , $( $rest:tt )* )? ) => {
+        $crate::tuple!(__pair $i, [ $( $done )* $name => $f!( $( $args )* ), ], [ $( $names )* ], $( $( $rest )* )?)
+    };
+
+    (__pair $i:expr, [ $( $done:tt )* ], [ $name:ident $( $names:ident )* ], $f:ident $( , $( $rest:tt )* )? ) => {
+        $crate::tuple!(__pair $i, [ $( $done )* $name => $f, ], [ $( $names )* ], $( $( $rest )* )?)
+    };
+
+    (__pair $i:expr, [ $( $done:tt )* ], [ $( $names:ident )* ], ) => {
+        $crate::tuple!(__impl $i, [ $( $done )* ])
+    };
+
+    (__pair $i:expr, [ $( $done:tt )* ], [], $( $rest:tt )+ ) => {
+        compile_error!("tuple! supports at most 12 sub parsers")
+    };
+
+    (__impl $i:expr, [ $( $name:ident => $item:tt $( ! $args:tt )?, )+ ]) => {
+        $crate::do_each!($i, $( $name => $item $( ! $args )?, )+ ( $( $name, )+ ))
+    };
+
+    ($i:expr, $( $rest:tt )+ ) => {
+        $crate::tuple!(__pair $i, [], [__t0 __t1 __t2 __t3 __t4 __t5 __t6 __t7 __t8 __t9 __t10 __t11], $( $rest )+)
+    };
+}
+
 /// Returns the output of the first sub parser to succeed.
 ///
 /// ```
@@ -489,7 +1216,7 @@ macro_rules! either {
 
     // Termination clause
     (__impl $i:expr, $f:ident!( $( $args:tt )* ),) => { // 6
-        $crate::either!(__impl $i, $f!($($args)*) __end)
+        $crate::either!(__impl $i, $f!($($args)*))
     };
 
     // Termination clause
@@ -542,400 +1269,2878 @@ macro_rules! either {
     }}
 }
 
-/// Maps a `Result` to be optional.
-///
-/// `Result::Fail` maps to None and `Result::Complete` maps to Some. The rest of the
-/// `Result` variants are left untouched. You must pass in the iterator that the
-/// next matcher should use in the event of a fail.
+/// Like `either!` but uses `Seekable` to return to the starting offset between
+/// sub parsers instead of cloning the input for each one.
 ///
-/// The `optional!` macro provides some syntactice sugar for using this combinator
-/// properly.
-pub fn optional<I, O>(iter: I, result: Result<I, O>) -> Result<I, Option<O>>
-where
-    I: InputIter,
-{
-    match result {
-        Result::Complete(i, o) => Result::Complete(i, Some(o)),
-        // Incomplete could still work possibly parse.
-        Result::Incomplete(ctx) => Result::Incomplete(ctx),
-        // Fail just means it didn't match.
-        Result::Fail(_) => Result::Complete(iter, None),
-        // Aborts are hard failures that the parser can't recover from.
-        Result::Abort(e) => Result::Abort(e),
-    }
-}
-
-/// Treats a sub parser as optional. It returns Some(output) for a successful match
-/// and None for failures.
+/// The iterator is taken from each failures error context and then seeked back
+/// to the starting offset before trying the next sub parser.
 ///
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
 /// use abortable_parser::iter;
 /// # use abortable_parser::{Result, Offsetable};
 /// # fn main() {
-/// let input_str = "foo";
-/// let iter = iter::SliceIter::new(input_str.as_bytes());
-/// let result = optional!(iter, text_token!("foo"));
+/// let iter = iter::StrIter::new("foo");
+/// let result = either_seek!(iter, text_token!("bar"), text_token!("foo"));
 /// # assert!(result.is_complete());
-/// # if let Result::Complete(_, o) = result {
-/// #     assert_eq!("foo", o.unwrap());
-/// # } else {
-/// #     assert!(false, "optional! did not complete");
-/// # }
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(i.get_offset(), 3);
+///     assert_eq!(o, "foo");
+/// }
 /// # }
+/// ```
 #[macro_export]
-macro_rules! optional {
-    ($i:expr, $f:ident) => {{
-        use $crate::run;
-        $crate::optional!(__impl $i, run!($f))
-    }};
+macro_rules! either_seek {
+    (__list $i:expr, $start:ident, [ $( $done:tt )* ], $f:ident!( $( $args:tt )* ) $( , $( $rest:tt )* )? ) => {
+        $crate::either_seek!(__list $i, $start, [ $( $done )* $f!( $( $args )* ), ], $( $( $rest )* )?)
+    };
 
-    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {
-        $crate::optional!(__impl $i, $f!( $( $args )* ))
+    (__list $i:expr, $start:ident, [ $( $done:tt )* ], $f:ident $( , $( $rest:tt )* )? ) => {
+        $crate::either_seek!(__list $i, $start, [ $( $done )* run!($f), ], $( $( $rest )* )?)
     };
 
-    (__impl $i:expr, $f:ident!( $( $args:tt )* )) => {{
-        let _i = $i.clone();
-       $crate::combinators::optional(_i, $f!($i, $($args)*))
+    (__list $i:expr, $start:ident, [ $( $done:tt )* ], ) => {
+        $crate::either_seek!(__impl $i, $start, $( $done )*)
+    };
+
+    // Termination clause
+    (__impl $i:expr, $start:ident, $f:ident!( $( $args:tt )* ), ) => {
+        $f!($i, $($args)*)
+    };
+
+    // Internal Loop Implementation
+    (__impl $i:expr, $start:ident, $f:ident!( $( $args:tt )* ), $( $rest:tt )+ ) => {{
+        use $crate::{Result, Seekable};
+        match $f!($i, $($args)*) {
+            // The first one to match is our result.
+            Result::Complete(i, o) => Result::Complete(i, o),
+            // Incompletes may still be parseable.
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            // Fail means try the next one from the starting offset.
+            Result::Fail(e) => {
+                let mut _i = e.into_context();
+                _i.seek($start);
+                $crate::either_seek!(__impl _i, $start, $( $rest )+)
+            }
+            // Aborts are hard failures that the parser can't recover from.
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $( $rest:tt )+ ) => {{
+        #[allow(unused_imports)]
+        use $crate::run;
+        use $crate::Offsetable;
+        let start_offset = $i.get_offset();
+        $crate::either_seek!(__list $i, start_offset, [], $( $rest )+)
     }};
 }
 
-/// Runs a single matcher repeating 0 or more times and returns a possibly empty
-/// vector of the parsed results.
+/// Like `either!` but an Incomplete from a sub parser is treated as a Fail so the
+/// next sub parser gets a chance to match.
+///
+/// This is what you usually want when parsing input that is already entirely
+/// in memory.
 ///
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
 /// use abortable_parser::iter;
 /// # use abortable_parser::{Result, Offsetable};
 /// # fn main() {
-/// let input_str = "foofoo";
-/// let iter = iter::SliceIter::new(input_str.as_bytes());
-/// let result = repeat!(iter, text_token!("foo"));
+/// let iter = iter::StrIter::new("foo");
+/// let result = alt_complete!(iter, until!(text_token!(";")), text_token!("foo"));
 /// # assert!(result.is_complete());
-/// if let Result::Complete(_, o) = result {
-///     assert_eq!(2, o.len());
-///     assert_eq!("foo", o[0]);
-///     assert_eq!("foo", o[1]);
-/// }
 /// # }
 /// ```
 #[macro_export]
-macro_rules! repeat {
-    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
-        use $crate::Result;
-        let mut _i = $i.clone();
-        let mut seq = Vec::new();
-        let mut opt_error = None;
-        loop {
-            let __i = _i.clone();
-            match $f!(_i, $($args)*) {
-                Result::Complete(i, o) => {
-                    seq.push(o);
-                    _i = i;
-                }
-                // Aborts are always a hard fail.
-                Result::Abort(e) => {
-                    opt_error = Some(Result::Abort(e));
-                    _i = $i.clone();
-                    break;
-                }
-                // Everything else just means we are finished parsing.
-                Result::Incomplete(_) => {
-                    _i = __i;
-                    break;
-                }
-                Result::Fail(_) => {
-                    _i = __i;
-                    break;
-                }
-            }
-        }
-        match opt_error {
-            Some(e) => e,
-            None => Result::Complete(_i, seq),
-        }
-    }};
+macro_rules! alt_complete {
+    (__wrap $i:expr, [ $( $done:tt )* ], $f:ident!( $( $args:tt )* ) $( , $( $rest:tt )* )? ) => {
+        $crate::alt_complete!(__wrap $i, [ $( $done )* complete!("Incomplete alternative", $f!( $( $args )* )), ], $( $( $rest )* )?)
+    };
 
-    ($i:expr, $f:ident) => {{
-        use $crate::run;
-        $crate::repeat!($i, run!($f))
+    (__wrap $i:expr, [ $( $done:tt )* ], $f:ident $( , $( $rest:tt )* )? ) => {
+        $crate::alt_complete!(__wrap $i, [ $( $done )* complete!("Incomplete alternative", run!($f)), ], $( $( $rest )* )?)
+    };
+
+    (__wrap $i:expr, [ $( $done:tt )* ], ) => {{
+        #[allow(unused_imports)]
+        use $crate::{complete, run};
+        $crate::either!($i, $( $done )*)
     }};
+
+    ($i:expr, $( $rest:tt )+ ) => {
+        $crate::alt_complete!(__wrap $i, [], $( $rest )+)
+    };
 }
 
-/// Parses separated list of items.
+/// Runs every sub parser against the same input and returns the Complete result
+/// that consumed the most input.
+///
+/// Ties go to the earliest sub parser. An Abort from any sub parser is returned
+/// immediately. If nothing completes then the first Incomplete is returned, or a
+/// Fail if there was none.
 ///
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
 /// use abortable_parser::iter;
 /// # use abortable_parser::{Result, Offsetable};
 /// # fn main() {
-/// let input_str = "foo,foo";
-/// let iter = iter::SliceIter::new(input_str.as_bytes());
-/// let result = separated!(iter, text_token!(","), text_token!("foo"));
+/// let iter = iter::SliceIter::new("foobar".as_bytes());
+/// let result = longest!(iter, text_token!("foo"), text_token!("foobar"));
 /// # assert!(result.is_complete());
-/// if let Result::Complete(_, o) = result {
-///     assert_eq!(2, o.len());
-///     assert_eq!("foo", o[0]);
-///     assert_eq!("foo", o[1]);
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(i.get_offset(), 6);
+///     assert_eq!(o, "foobar");
 /// }
 /// # }
 /// ```
 #[macro_export]
-macro_rules! separated {
-    ($i:expr, $sep_rule:ident!( $( $sep_args:tt )* ), $item_rule:ident!( $( $item_args:tt )* ) ) => {{
-        use $crate::Result;
-        let _i = $i.clone();
-        // We require at least one item for our list
-        let head =  $item_rule!($i.clone(), $($item_args)*);
-        match head {
-            Result::Incomplete(ctx) => Result::Incomplete(ctx),
-            Result::Fail(e) => Result::Fail(e),
-            Result::Abort(e) => Result::Abort(e),
-            Result::Complete(i,item) => {
-                let mut list = vec![item];
-                // Now we parse a repeat of sep_rule and item_rule.
-                let tail_result = repeat!(i,
-                    do_each!(
-                        _    => $sep_rule!($($sep_args)*),
-                        item => $item_rule!($($item_args)*),
-                        (item)
-                    )
-                );
-                match tail_result {
-                    Result::Fail(e) => Result::Fail(e),
-                    Result::Incomplete(ctx) => Result::Incomplete(ctx),
-                    Result::Abort(e) => Result::Abort(e),
-                    Result::Complete(i, mut tail) => {
-                        list.extend(tail.drain(0..));
-                        Result::Complete(i, list)
+macro_rules! longest {
+    (__list $i:expr, [ $( $done:tt )* ], $f:ident!( $( $args:tt )* ) $( , $( $rest:tt )* )? ) => {
+        $crate::longest!(__list $i, [ $( $done )* $f!( $( $args )* ), ], $( $( $rest )* )?)
+    };
+
+    (__list $i:expr, [ $( $done:tt )* ], $f:ident $( , $( $rest:tt )* )? ) => {
+        $crate::longest!(__list $i, [ $( $done )* run!($f), ], $( $( $rest )* )?)
+    };
+
+    (__list $i:expr, [ $( $done:tt )* ], ) => {
+        $crate::longest!(__impl $i, $( $done )*)
+    };
+
+    (__impl $i:expr, $( $f:ident!( $( $args:tt )* ), )+ ) => {{
+        #[allow(unused_imports)]
+        use $crate::run;
+        use $crate::{Error, Offsetable, Result};
+        let mut best = None;
+        let mut incomplete = None;
+        let mut abort = None;
+        $(
+            if abort.is_none() {
+                match $f!($i.clone(), $( $args )*) {
+                    Result::Complete(i, o) => {
+                        best = match best.take() {
+                            Some((bi, bo)) if Offsetable::get_offset(&bi) >= i.get_offset() => Some((bi, bo)),
+                            _ => Some((i, o)),
+                        };
                     }
+                    Result::Incomplete(ctx) => {
+                        if incomplete.is_none() {
+                            incomplete = Some(ctx);
+                        }
+                    }
+                    Result::Fail(_) => {}
+                    Result::Abort(e) => abort = Some(e),
                 }
             }
+        )+
+        match (abort, best, incomplete) {
+            (Some(e), _, _) => Result::Abort(e),
+            (None, Some((i, o)), _) => Result::Complete(i, o),
+            (None, None, Some(ctx)) => Result::Incomplete(ctx),
+            (None, None, None) => Result::Fail(Error::new(
+                "No sub parser matched",
+                Box::new($i.clone()),
+            )),
         }
     }};
 
-    ($i:expr, $sep_rule:ident, $item_rule:ident ) => {{
-        use $crate::run;
-        $crate::separated!($i, run!($sep_rule), run!($item_rule))
-    }};
-
-    ($i:expr, $sep_rule:ident!( $( $args:tt )* ), $item_rule:ident ) => {{
-        use $crate::run;
-        $crate::separated!($i, $sep_rule!($($args)*), run!($item_rule))
-    }};
-
-    ($i:expr, $sep_rule:ident, $item_rule:ident!( $( $args:tt )* ) ) => {{
-        use $crate::run;
-        $crate::separated!($i, run!($sep_rule), $item_rule!($($args)*))
-    }};
+    ($i:expr, $( $rest:tt )+ ) => {
+        $crate::longest!(__list $i, [], $( $rest )+)
+    };
 }
 
-/// Convenience macro for looking for a specific text token in a byte input stream.
+/// Returns the zero based index and output of the first sub parser to succeed.
+///
+/// All of the sub parsers must have the same output type. Supports up to 12
+/// sub parsers.
 ///
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
 /// use abortable_parser::iter;
 /// # use abortable_parser::{Result, Offsetable};
-/// use std::convert::From;
 /// # fn main() {
-/// let iter: iter::SliceIter<u8> = "foo bar".into();
-/// let tok = text_token!(iter, "foo");
-/// # assert!(tok.is_complete());
-/// if let Result::Complete(i, o) = tok {
-///     assert_eq!(i.get_offset(), 3);
-///     assert_eq!(o, "foo");
+/// let iter = iter::StrIter::new("bar");
+/// let result = first_of!(iter, text_token!("foo"), text_token!("bar"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, (idx, o)) = result {
+///     assert_eq!(idx, 1);
+///     assert_eq!(o, "bar");
 /// }
 /// # }
 /// ```
 #[macro_export]
-macro_rules! text_token {
-    ($i:expr, $e:expr) => {{
-        use $crate::Error;
-        use $crate::Result;
-        let mut _i = $i.clone();
-        let mut count = 0;
-        for expected in $e.bytes() {
-            let item = match _i.next() {
-                Some(item) => item,
-                None => break,
-            };
-            if item == &expected {
-                count += 1;
-            }
-        }
-        if count == $e.len() {
-            Result::Complete(_i.clone(), $e)
-        } else {
+macro_rules! first_of {
+    (__list $i:expr, [ $( $done:tt )* ], [ $idx:tt $( $idxs:tt )* ], $f:ident!( $( $args:tt )* ) $( , $( $rest:tt )* )? ) => {
+        $crate::first_of!(__list $i, [ $( $done )* ($idx, $f!( $( $args )* )) ], [ $( $idxs )* ], $( $( $rest )* )?)
+    };
+
+    (__list $i:expr, [ $( $done:tt )* ], [ $idx:tt $( $idxs:tt )* ], $f:ident $( , $( $rest:tt )* )? ) => {
+        $crate::first_of!(__list $i, [ $( $done )* ($idx, run!($f)) ], [ $( $idxs )* ], $( $( $rest )* )?)
+    };
+
+    (__list $i:expr, [ $( $done:tt )* ], [ $( $idxs:tt )* ], ) => {
+        $crate::first_of!(__impl $i, $( $done )*)
+    };
+
+    (__list $i:expr, [ $( $done:tt )* ], [], $( $rest:tt )+ ) => {
+        compile_error!("first_of! supports at most 12 sub parsers")
+    };
+
+    (__impl $i:expr, $( ($idx:tt, $f:ident!( $( $args:tt )* )) )+ ) => {{
+        #[allow(unused_imports)]
+        use $crate::run;
+        use $crate::{Error, Result};
+        (|| {
+            $(
+                match $f!($i.clone(), $( $args )*) {
+                    Result::Complete(i, o) => {
+                        let idx: usize = $idx;
+                        return Result::Complete(i, (idx, o));
+                    }
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    // Fail means it didn't match so continue to next one.
+                    Result::Fail(_) => {}
+                    Result::Abort(e) => return Result::Abort(e),
+                }
+            )+
             Result::Fail(Error::new(
-                format!("Expected {} but didn't get it.", $e),
+                "No sub parser matched",
                 Box::new($i.clone()),
             ))
-        }
+        })()
     }};
+
+    ($i:expr, $( $rest:tt )+ ) => {
+        $crate::first_of!(__list $i, [], [0 1 2 3 4 5 6 7 8 9 10 11], $( $rest )+)
+    };
 }
 
-/// Consumes an input until it reaches a term that the contained rule matches.
-/// It does not consume the subrule.
+/// Matches each sub parser exactly once in any order and returns a tuple of
+/// their outputs in the order the sub parsers were declared.
+///
+/// Every round tries each sub parser that has not matched yet so a Fail from an
+/// earlier sub parser lets a later one try. Fails if any sub parser never
+/// matches. Supports up to 12 sub parsers.
 ///
-/// If the term never matches then returns incomplete.
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
 /// use abortable_parser::iter;
 /// # use abortable_parser::{Result, Offsetable};
-/// use std::convert::From;
 /// # fn main() {
-/// let iter: iter::SliceIter<u8> = "foo;".into();
-/// let tok = until!(iter, text_token!(";"));
-/// # assert!(tok.is_complete());
-/// if let Result::Complete(i, o) = tok {
-///     assert_eq!(i.get_offset(), 3);
+/// let iter = iter::SliceIter::new("barfoo".as_bytes());
+/// let result = permutation!(iter, text_token!("foo"), text_token!("bar"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(i.get_offset(), 6);
+///     assert_eq!(o, ("foo", "bar"));
 /// }
 /// # }
 /// ```
 #[macro_export]
-macro_rules! until {
-    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
-        use $crate::{Result, Offsetable, Span, SpanRange};
-        let start_offset = $i.get_offset();
+macro_rules! permutation {
+    // Pair each sub parser with a slot name.
+    (__pair $i:expr, [ $( $done:tt )* ], [ $name:ident $( $names:ident )* ], $f:ident!( $( $args:tt )* ) $( , $( $rest:tt )* )? ) => {
+        $crate::permutation!(__pair $i, [ $( $done )* ($name, $f!( $( $args )* )) ], [ $( $names )* ], $( $( $rest )* )?)
+    };
+
+    (__pair $i:expr, [ $( $done:tt )* ], [ $name:ident $( $names:ident )* ], $f:ident $( , $( $rest:tt )* )? ) => {
+        $crate::permutation!(__pair $i, [ $( $done )* ($name, run!($f)) ], [ $( $names )* ], $( $( $rest )* )?)
+    };
+
+    (__pair $i:expr, [ $( $done:tt )* ], [ $( $names:ident )* ], ) => {
+        $crate::permutation!(__impl $i, $( $done )*)
+    };
+
+    (__pair $i:expr, [ $( $done:tt )* ], [], $( $rest:tt )+ ) => {
+        compile_error!("permutation! supports at most 12 sub parsers")
+    };
+
+    (__impl $i:expr, $( ($name:ident, $f:ident!( $( $args:tt )* )) )+ ) => {{
+        #[allow(unused_imports)]
+        use $crate::run;
+        use $crate::{Error, Result};
         let mut _i = $i.clone();
-        let pfn = || {
-            loop {
-                match $rule!(_i.clone(), $($args)*) {
-                    Result::Complete(_, _) => {
-                        let range = SpanRange::Range(start_offset.._i.get_offset());
-                        return Result::Complete(_i, $i.span(range));
-                    },
-                    Result::Abort(e) => return Result::Abort(e),
-                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
-                    Result::Fail(_) => {
-                        // noop
+        $( let mut $name = None; )+
+        loop {
+            let mut progressed = false;
+            $(
+                if $name.is_none() {
+                    match $f!(_i.clone(), $( $args )*) {
+                        Result::Complete(i, o) => {
+                            _i = i;
+                            $name = Some(o);
+                            progressed = true;
+                        }
+                        // Another sub parser may match here instead.
+                        Result::Fail(_) => {}
+                        Result::Incomplete(ctx) => break Result::Incomplete(ctx),
+                        Result::Abort(e) => break Result::Abort(e),
                     }
                 }
-                if let None = _i.next() {
-                    return Result::Incomplete(_i.clone());
+            )+
+            if !progressed {
+                match ( $( $name, )+ ) {
+                    ( $( Some($name), )+ ) => break Result::Complete(_i, ( $( $name, )+ )),
+                    _ => break Result::Fail(Error::new(
+                        "Not all permutation sub parsers matched",
+                        Box::new(_i.clone()),
+                    )),
                 }
             }
-        };
-        pfn()
+        }
     }};
 
-    ($i:expr, $rule:ident) => {{
-        use $crate::run;
-        $crate::until!($i, run!($rule))
-    }};
+    ($i:expr, $( $rest:tt )+ ) => {
+        $crate::permutation!(__pair $i, [], [__p0 __p1 __p2 __p3 __p4 __p5 __p6 __p7 __p8 __p9 __p10 __p11], $( $rest )+)
+    };
 }
 
-/// Discards the output of a combinator rule when it completes and just returns `()`.
-/// Leaves Failures, Aborts, and Incompletes untouched.
+/// Parses a tag and then dispatches to the body parser registered for that tag,
+/// wrapping the bodies output with the given constructor.
+///
+/// Each arm is a pattern for the tag followed by the constructor applied to the
+/// body parser. An unknown tag is an Abort.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::{ascii_digit, ascii_alpha};
+/// #[derive(Debug, PartialEq)]
+/// enum Value {
+///     Num(u8),
+///     Word(u8),
+/// }
+///
+/// # fn main() {
+/// let iter = iter::SliceIter::new("n1".as_bytes());
+/// let result = tagged!(iter, ascii_alpha, {
+///     b'n' => Value::Num(ascii_digit),
+///     b'w' => Value::Word(ascii_alpha),
+/// });
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, Value::Num(b'1'));
+/// }
+/// # }
+/// ```
 #[macro_export]
-macro_rules! discard {
-    ($i:expr, $rule:ident) => {{
-        use $crate::run;
-        $crate::discard!($i, run!($rule))
-    }};
+macro_rules! tagged {
+    (__body $i:expr, $f:ident!( $( $args:tt )* )) => {
+        $f!($i, $($args)*)
+    };
 
-    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
-        use $crate::Result;
-        match $rule!($i, $($args)*) {
-            Result::Complete(i, _) => Result::Complete(i, ()),
+    (__body $i:expr, $f:ident) => {
+        $f($i)
+    };
+
+    ($i:expr, $tag_rule:ident!( $( $tag_args:tt )* ), { $( $tag:pat => $( $ctor:ident )::+ ( $body:ident $( !( $( $body_args:tt )* ) )? ) ),+ $(,)? }) => {{
+        use $crate::{Error, Result};
+        let _i = $i.clone();
+        match $tag_rule!($i, $($tag_args)*) {
+            Result::Complete(i, tag) => match tag {
+                $(
+                    $tag => match $crate::tagged!(__body i, $body $( !( $( $body_args )* ) )?) {
+                        Result::Complete(i, o) => Result::Complete(i, $( $ctor )::+(o)),
+                        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                        Result::Fail(e) => Result::Fail(e),
+                        Result::Abort(e) => Result::Abort(e),
+                    },
+                )+
+                #[allow(unreachable_patterns)]
+                _ => Result::Abort(Error::new("Unknown tag".to_string(), Box::new(_i.clone()))),
+            },
             Result::Incomplete(ctx) => Result::Incomplete(ctx),
             Result::Fail(e) => Result::Fail(e),
             Result::Abort(e) => Result::Abort(e),
         }
     }};
-}
 
-/// Matches and returns any ascii charactar whitespace byte.
-pub fn ascii_ws<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
-    match i.next() {
-        Some(b) => {
-            if (*b as char).is_whitespace() {
-                Result::Complete(i, *b)
-            } else {
-                Result::Fail(Error::new(
-                    "Not whitespace".to_string(),
-                    Box::new(i.clone()),
-                ))
-            }
-        }
-        None => Result::Fail(Error::new(
-            "Unexpected End Of Input".to_string(),
-            Box::new(i.clone()),
-        )),
-    }
+    ($i:expr, $tag_rule:ident, { $( $arms:tt )* }) => {{
+        use $crate::run;
+        $crate::tagged!($i, run!($tag_rule), { $($arms)* })
+    }};
 }
 
-/// Matches the end of input for any InputIter.
-/// Returns `()` for any match.
-pub fn eoi<I: InputIter>(i: I) -> Result<I, ()> {
-    let mut _i = i.clone();
-    match _i.next() {
-        Some(_) => Result::Fail(Error::new(
-            "Expected End Of Input".to_string(),
-            Box::new(i.clone()),
-        )),
-        None => Result::Complete(i, ()),
-    }
+/// Maps a `Result` to be optional.
+///
+/// `Result::Fail` maps to None and `Result::Complete` maps to Some. The rest of the
+/// `Result` variants are left untouched. You must pass in the iterator that the
+/// next matcher should use in the event of a fail.
+///
+/// The `optional!` macro provides some syntactice sugar for using this combinator
+/// properly.
+pub fn optional<I, O>(iter: I, result: Result<I, O>) -> Result<I, Option<O>>
+where
+    I: InputIter,
+{
+    match result {
+        Result::Complete(i, o) => Result::Complete(i, Some(o)),
+        // Incomplete could still work possibly parse.
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        // Fail just means it didn't match.
+        Result::Fail(_) => Result::Complete(iter, None),
+        // Aborts are hard failures that the parser can't recover from.
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
+/// Peeks at the next byte of the input and runs the parser for the first arm
+/// whose pattern matches it.
+///
+/// Only the selected parser is run which makes this cheaper than `either!` when
+/// the alternatives can be told apart by their first byte. Fails if no arm
+/// matches or the input is empty.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::{ascii_digit, ascii_alpha};
+/// # fn main() {
+/// let iter = iter::SliceIter::new("7".as_bytes());
+/// let result = dispatch!(iter, {
+///     b'0'..=b'9' => ascii_digit,
+///     _ => ascii_alpha,
+/// });
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, b'7');
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! dispatch {
+    (__body $i:expr, $f:ident!( $( $args:tt )* )) => {
+        $f!($i, $($args)*)
+    };
+
+    (__body $i:expr, $f:ident) => {
+        $f($i)
+    };
+
+    ($i:expr, { $( $byte:pat => $body:ident $( !( $( $body_args:tt )* ) )? ),+ $(,)? }) => {{
+        use $crate::{Error, Peekable, Result};
+        match $i.peek_next().map(|b: &u8| *b) {
+            $(
+                Some($byte) => $crate::dispatch!(__body $i, $body $( !( $( $body_args )* ) )?),
+            )+
+            #[allow(unreachable_patterns)]
+            _ => Result::Fail(Error::new(
+                "No dispatch arm matched the next byte".to_string(),
+                Box::new($i.clone()),
+            )),
+        }
+    }};
+}
+
+/// Returns the output of the first sub parser to succeed wrapped in the
+/// constructor given for that sub parser.
+///
+/// Each arm is a constructor such as an enum variant followed by the sub parser
+/// whose output it wraps. Fails if none of the sub parsers match.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::{ascii_digit, ascii_alpha};
+/// #[derive(Debug, PartialEq)]
+/// enum Value {
+///     Num(u8),
+///     Word(u8),
+/// }
+///
+/// # fn main() {
+/// let iter = iter::SliceIter::new("w".as_bytes());
+/// let result = tagged_either!(iter, {
+///     Value::Num => ascii_digit,
+///     Value::Word => ascii_alpha,
+/// });
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, Value::Word(b'w'));
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! tagged_either {
+    (__body $i:expr, $f:ident!( $( $args:tt )* )) => {
+        $f!($i, $($args)*)
+    };
+
+    (__body $i:expr, $f:ident) => {
+        $f($i)
+    };
+
+    ($i:expr, { $( $( $ctor:ident )::+ => $body:ident $( !( $( $body_args:tt )* ) )? ),+ $(,)? }) => {{
+        use $crate::{Error, Result};
+        (|| {
+            $(
+                match $crate::tagged_either!(__body $i.clone(), $body $( !( $( $body_args )* ) )?) {
+                    Result::Complete(i, o) => return Result::Complete(i, $( $ctor )::+(o)),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    // Fail means it didn't match so continue to next one.
+                    Result::Fail(_) => {}
+                    Result::Abort(e) => return Result::Abort(e),
+                }
+            )+
+            Result::Fail(Error::new(
+                "No sub parser matched",
+                Box::new($i.clone()),
+            ))
+        })()
+    }};
+}
+
+/// Treats a sub parser as optional. It returns Some(output) for a successful match
+/// and None for failures.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foo";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = optional!(iter, text_token!("foo"));
+/// # assert!(result.is_complete());
+/// # if let Result::Complete(_, o) = result {
+/// #     assert_eq!("foo", o.unwrap());
+/// # } else {
+/// #     assert!(false, "optional! did not complete");
+/// # }
+/// # }
+#[macro_export]
+macro_rules! optional {
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::optional!(__impl $i, run!($f))
+    }};
+
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {
+        $crate::optional!(__impl $i, $f!( $( $args )* ))
+    };
+
+    (__impl $i:expr, $f:ident!( $( $args:tt )* )) => {{
+        let _i = $i.clone();
+       $crate::combinators::optional(_i, $f!($i, $($args)*))
+    }};
+}
+
+/// Treats a sub parser producing a `Vec` as optional. It returns the output for a
+/// successful match and an empty `Vec` for failures.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::SliceIter::new("bar".as_bytes());
+/// let result = opt_vec!(iter, separated!(text_token!(","), text_token!("foo")));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(i.get_offset(), 0);
+///     assert!(o.is_empty());
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! opt_vec {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Result;
+        let _i = $i.clone();
+        match $crate::combinators::optional(_i, $f!($i, $($args)*)) {
+            Result::Complete(i, o) => Result::Complete(i, o.unwrap_or_else(Vec::new)),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::opt_vec!($i, run!($f))
+    }};
+}
+
+/// Treats a sub parser as optional like `optional!` but uses `Seekable` to
+/// restore the position on a failed match instead of cloning the input up front.
+///
+/// The iterator is taken from the failures error context and then seeked back
+/// to the starting offset.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = optional_seek!(iter, text_token!("bar"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(i.get_offset(), 0);
+///     assert!(o.is_none());
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! optional_seek {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Offsetable, Result, Seekable};
+        let start_offset = $i.get_offset();
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => Result::Complete(i, Some(o)),
+            // Incomplete could still work possibly parse.
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            // Fail just means it didn't match so we seek back to where we started.
+            Result::Fail(e) => {
+                let mut _i = e.into_context();
+                _i.seek(start_offset);
+                Result::Complete(_i, None)
+            }
+            // Aborts are hard failures that the parser can't recover from.
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::optional_seek!($i, run!($f))
+    }};
+}
+
+/// Runs a single matcher repeating 0 or more times and returns a possibly empty
+/// vector of the parsed results.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foofoo";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = repeat!(iter, text_token!("foo"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(2, o.len());
+///     assert_eq!("foo", o[0]);
+///     assert_eq!("foo", o[1]);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! repeat {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {
+        $crate::fold_many!(
+            $i,
+            Vec::new(),
+            |mut seq: Vec<_>, o| {
+                seq.push(o);
+                seq
+            },
+            $f!($($args)*)
+        )
+    };
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::repeat!($i, run!($f))
+    }};
+}
+
+/// Like `repeat!` but collects the outputs into any `Default + Extend` collection
+/// such as a `String` or a `HashSet` instead of a `Vec`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter::SliceIter;
+/// use abortable_parser::combinators::ascii_digit;
+/// use abortable_parser::{Result, Offsetable};
+/// use std::collections::BTreeSet;
+/// # fn main() {
+/// let iter = SliceIter::new("3113a".as_bytes());
+/// let result = repeat_into!(iter, BTreeSet<u8>, ascii_digit);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(i.get_offset(), 4);
+///     assert_eq!(o.len(), 2);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! repeat_into {
+    ($i:expr, $collection_ty:ty, $f:ident!( $( $args:tt )* ) ) => {{
+        let collection: $collection_ty = Default::default();
+        $crate::fold_many!(
+            $i,
+            collection,
+            |mut collection: $collection_ty, o| {
+                Extend::extend(&mut collection, Some(o));
+                collection
+            },
+            $f!($($args)*)
+        )
+    }};
+
+    ($i:expr, $collection_ty:ty, $f:ident) => {{
+        use $crate::run;
+        $crate::repeat_into!($i, $collection_ty, run!($f))
+    }};
+}
+
+/// Like `repeat!` but discards the outputs instead of collecting them.
+///
+/// Useful for skipping whitespace or comments without allocating.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter::StrIter;
+/// use abortable_parser::combinators::ascii_ws;
+/// use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = StrIter::new("  foo");
+/// let result = skip_many!(iter, ascii_ws);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, ()) = result {
+///     assert_eq!(i.get_offset(), 2);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! skip_many {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {
+        $crate::fold_many!($i, (), |_, _| (), $f!($($args)*))
+    };
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::skip_many!($i, run!($f))
+    }};
+}
+
+/// Runs a matcher repeating 0 or more times where the matcher for each
+/// repetition is chosen by calling a function with the zero based index of
+/// the repetition. Returns a possibly empty vector of the parsed results.
+///
+/// The function must return a parser function such as a `fn` pointer or a closure.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter::StrIter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::{ascii_alpha, ascii_digit};
+/// # fn main() {
+/// let iter = StrIter::new("1ab");
+/// let result = repeat_indexed!(iter, |idx| -> fn(StrIter<'static>) -> Result<StrIter<'static>, u8> {
+///     if idx == 0 { ascii_digit } else { ascii_alpha }
+/// });
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(vec![b'1', b'a', b'b'], o);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! repeat_indexed {
+    ($i:expr, $select:expr) => {{
+        let select = $select;
+        // The loop stops at the first match that doesn't complete so the
+        // number of calls so far is the index of the repetition.
+        let mut index = 0;
+        let mut rule = |i| {
+            let rule = select(index);
+            index += 1;
+            rule(i)
+        };
+        $crate::fold_many!(
+            $i,
+            Vec::new(),
+            |mut seq: Vec<_>, o| {
+                seq.push(o);
+                seq
+            },
+            rule
+        )
+    }};
+}
+
+/// Runs a single matcher repeating 0 or more times and folds each output into
+/// an accumulator instead of collecting them into a vector.
+///
+/// The accumulator function is called with the current accumulator and the
+/// output and returns the new accumulator.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foofoo";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = fold_many!(iter, 0, |acc, _| acc + 1, text_token!("foo"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, count) = result {
+///     assert_eq!(2, count);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fold_many {
+    ($i:expr, $init:expr, $acc:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Result;
+        let mut _i = $i.clone();
+        let mut acc = $init;
+        let mut opt_error = None;
+        loop {
+            let __i = _i.clone();
+            match $f!(_i, $($args)*) {
+                Result::Complete(i, o) => {
+                    acc = ($acc)(acc, o);
+                    _i = i;
+                }
+                // Aborts are always a hard fail.
+                Result::Abort(e) => {
+                    opt_error = Some(Result::Abort(e));
+                    _i = $i.clone();
+                    break;
+                }
+                // Everything else just means we are finished parsing.
+                Result::Incomplete(_) => {
+                    _i = __i;
+                    break;
+                }
+                Result::Fail(_) => {
+                    _i = __i;
+                    break;
+                }
+            }
+        }
+        match opt_error {
+            Some(e) => e,
+            None => Result::Complete(_i, acc),
+        }
+    }};
+
+    ($i:expr, $init:expr, $acc:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::fold_many!($i, $init, $acc, run!($f))
+    }};
+}
+
+/// Runs a single matcher repeating 0 or more times like `repeat!` and returns
+/// the number of times it matched.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foofoo";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = count_matches!(iter, text_token!("foo"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, count) = result {
+///     assert_eq!(2, count);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! count_matches {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {
+        $crate::fold_many!($i, 0usize, |count, _| count + 1, $f!($($args)*))
+    };
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::count_matches!($i, run!($f))
+    }};
+}
+
+/// Parses separated list of items.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foo,foo";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = separated!(iter, text_token!(","), text_token!("foo"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(2, o.len());
+///     assert_eq!("foo", o[0]);
+///     assert_eq!("foo", o[1]);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! separated {
+    ($i:expr, $sep_rule:ident!( $( $sep_args:tt )* ), $item_rule:ident!( $( $item_args:tt )* ) ) => {{
+        use $crate::Result;
+        let _i = $i.clone();
+        // We require at least one item for our list
+        let head =  $item_rule!($i.clone(), $($item_args)*);
+        match head {
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+            Result::Complete(i,item) => {
+                let mut list = vec![item];
+                // Now we parse a repeat of sep_rule and item_rule.
+                let tail_result = repeat!(i,
+                    do_each!(
+                        _    => $sep_rule!($($sep_args)*),
+                        item => $item_rule!($($item_args)*),
+                        (item)
+                    )
+                );
+                match tail_result {
+                    Result::Fail(e) => Result::Fail(e),
+                    Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                    Result::Abort(e) => Result::Abort(e),
+                    Result::Complete(i, mut tail) => {
+                        list.extend(tail.drain(0..));
+                        Result::Complete(i, list)
+                    }
+                }
+            }
+        }
+    }};
+
+    ($i:expr, $sep_rule:ident, $item_rule:ident ) => {{
+        use $crate::run;
+        $crate::separated!($i, run!($sep_rule), run!($item_rule))
+    }};
+
+    ($i:expr, $sep_rule:ident!( $( $args:tt )* ), $item_rule:ident ) => {{
+        use $crate::run;
+        $crate::separated!($i, $sep_rule!($($args)*), run!($item_rule))
+    }};
+
+    ($i:expr, $sep_rule:ident, $item_rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::separated!($i, run!($sep_rule), $item_rule!($($args)*))
+    }};
+}
+
+/// Parses a list of items that are each followed by a terminator. Returns a
+/// possibly empty vector of the item outputs.
+///
+/// Stops at the first item that doesn't match along with its terminator.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo;foo;");
+/// let result = terminated_list!(iter, text_token!("foo"), text_token!(";"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(vec!["foo", "foo"], o);
+///     assert_eq!(i.get_offset(), 8);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! terminated_list {
+    ($i:expr, $item_rule:ident!( $( $item_args:tt )* ), $term_rule:ident!( $( $term_args:tt )* ) ) => {
+        $crate::repeat!($i,
+            do_each!(
+                item => $item_rule!($($item_args)*),
+                _    => $term_rule!($($term_args)*),
+                (item)
+            )
+        )
+    };
+
+    ($i:expr, $item_rule:ident, $term_rule:ident ) => {{
+        use $crate::run;
+        $crate::terminated_list!($i, run!($item_rule), run!($term_rule))
+    }};
+
+    ($i:expr, $item_rule:ident!( $( $args:tt )* ), $term_rule:ident ) => {{
+        use $crate::run;
+        $crate::terminated_list!($i, $item_rule!($($args)*), run!($term_rule))
+    }};
+
+    ($i:expr, $item_rule:ident, $term_rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::terminated_list!($i, run!($item_rule), $term_rule!($($args)*))
+    }};
+}
+
+/// Parses a separated list of items like `separated!` but also consumes an
+/// optional trailing separator.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foo,foo,";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = separated_trailing!(iter, text_token!(","), text_token!("foo"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(2, o.len());
+///     assert_eq!(i.get_offset(), 8);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! separated_trailing {
+    ($i:expr, $sep_rule:ident!( $( $sep_args:tt )* ), $item_rule:ident!( $( $item_args:tt )* ) ) => {{
+        use $crate::Result;
+        match $crate::separated!($i, $sep_rule!($($sep_args)*), $item_rule!($($item_args)*)) {
+            Result::Complete(i, list) => {
+                match $crate::optional!(i, $sep_rule!($($sep_args)*)) {
+                    Result::Complete(i, _) => Result::Complete(i, list),
+                    Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                    Result::Fail(e) => Result::Fail(e),
+                    Result::Abort(e) => Result::Abort(e),
+                }
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $sep_rule:ident, $item_rule:ident ) => {{
+        use $crate::run;
+        $crate::separated_trailing!($i, run!($sep_rule), run!($item_rule))
+    }};
+
+    ($i:expr, $sep_rule:ident!( $( $args:tt )* ), $item_rule:ident ) => {{
+        use $crate::run;
+        $crate::separated_trailing!($i, $sep_rule!($($args)*), run!($item_rule))
+    }};
+
+    ($i:expr, $sep_rule:ident, $item_rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::separated_trailing!($i, run!($sep_rule), $item_rule!($($args)*))
+    }};
+}
+
+/// Parses a separated list of items folding each item into an accumulator
+/// instead of collecting them into a vector.
+///
+/// Like `separated!` this requires at least one item.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let input_str = "foo,foo";
+/// let iter = iter::SliceIter::new(input_str.as_bytes());
+/// let result = separated_fold!(iter, text_token!(","), text_token!("foo"), 0, |acc, _| acc + 1);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, count) = result {
+///     assert_eq!(2, count);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! separated_fold {
+    ($i:expr, $sep_rule:ident!( $( $sep_args:tt )* ), $item_rule:ident!( $( $item_args:tt )* ), $init:expr, $acc:expr ) => {{
+        use $crate::Result;
+        // We require at least one item for our list
+        match $item_rule!($i.clone(), $($item_args)*) {
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+            Result::Complete(i, item) => {
+                let acc_fn = $acc;
+                let init = acc_fn($init, item);
+                // Now we fold a repeat of sep_rule and item_rule.
+                $crate::fold_many!(i, init, acc_fn,
+                    do_each!(
+                        _    => $sep_rule!($($sep_args)*),
+                        item => $item_rule!($($item_args)*),
+                        (item)
+                    )
+                )
+            }
+        }
+    }};
+
+    ($i:expr, $sep_rule:ident, $item_rule:ident, $init:expr, $acc:expr ) => {{
+        use $crate::run;
+        $crate::separated_fold!($i, run!($sep_rule), run!($item_rule), $init, $acc)
+    }};
+
+    ($i:expr, $sep_rule:ident!( $( $args:tt )* ), $item_rule:ident, $init:expr, $acc:expr ) => {{
+        use $crate::run;
+        $crate::separated_fold!($i, $sep_rule!($($args)*), run!($item_rule), $init, $acc)
+    }};
+
+    ($i:expr, $sep_rule:ident, $item_rule:ident!( $( $args:tt )* ), $init:expr, $acc:expr ) => {{
+        use $crate::run;
+        $crate::separated_fold!($i, run!($sep_rule), $item_rule!($($args)*), $init, $acc)
+    }};
+}
+
+/// Parses zero or more items until a terminator matches. Returns a tuple of the
+/// list of items and the terminators output.
+///
+/// The terminator is tried before each item so if it matches immediately the
+/// list is empty.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # fn main() {
+/// let iter = iter::StrIter::new("]");
+/// let result = many_till!(iter, ascii_alpha, text_token!("]"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, (items, term)) = result {
+///     assert!(items.is_empty());
+///     assert_eq!("]", term);
+///     assert_eq!(i.get_offset(), 1);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! many_till {
+    ($i:expr, $item_rule:ident!( $( $item_args:tt )* ), $term_rule:ident!( $( $term_args:tt )* ) ) => {{
+        use $crate::Result;
+        let mut _i = $i.clone();
+        let mut list = Vec::new();
+        (|| {
+            loop {
+                match $term_rule!(_i.clone(), $($term_args)*) {
+                    Result::Complete(i, term) => return Result::Complete(i, (list, term)),
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    Result::Fail(_) => {
+                        // noop
+                    }
+                }
+                match $item_rule!(_i.clone(), $($item_args)*) {
+                    Result::Complete(i, item) => {
+                        list.push(item);
+                        _i = i;
+                    }
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    Result::Fail(e) => return Result::Fail(e),
+                }
+            }
+        })()
+    }};
+
+    ($i:expr, $item_rule:ident, $term_rule:ident ) => {{
+        use $crate::run;
+        $crate::many_till!($i, run!($item_rule), run!($term_rule))
+    }};
+
+    ($i:expr, $item_rule:ident!( $( $args:tt )* ), $term_rule:ident ) => {{
+        use $crate::run;
+        $crate::many_till!($i, $item_rule!($($args)*), run!($term_rule))
+    }};
+
+    ($i:expr, $item_rule:ident, $term_rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::many_till!($i, run!($item_rule), $term_rule!($($args)*))
+    }};
+}
+
+/// Parses operands separated by a binary operator and folds them together
+/// left associatively with `$combine(left, op, right)`.
+///
+/// Stops at the first operator that isn't followed by an operand. Fails if
+/// there isn't at least one operand.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::signed_integer;
+/// # fn main() {
+/// let iter = iter::StrIter::new("8/2/2");
+/// let result = left_assoc!(iter, signed_integer, text_token!("/"), |l, _, r| l / r);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, 2);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! left_assoc {
+    ($i:expr, $operand:ident!( $( $operand_args:tt )* ), $operator:ident!( $( $operator_args:tt )* ), $combine:expr ) => {{
+        use $crate::Result;
+        match $operand!($i.clone(), $($operand_args)*) {
+            Result::Complete(i, first) => {
+                let combine = $combine;
+                let mut _i = i;
+                let mut acc = first;
+                let mut opt_error = None;
+                loop {
+                    let pair = $crate::do_each!(_i.clone(),
+                        op => $operator!($($operator_args)*),
+                        right => $operand!($($operand_args)*),
+                        ((op, right))
+                    );
+                    match pair {
+                        Result::Complete(i, (op, right)) => {
+                            acc = combine(acc, op, right);
+                            _i = i;
+                        }
+                        // Aborts are always a hard fail.
+                        Result::Abort(e) => {
+                            opt_error = Some(e);
+                            break;
+                        }
+                        // Everything else just means we are finished parsing.
+                        Result::Incomplete(_) | Result::Fail(_) => break,
+                    }
+                }
+                match opt_error {
+                    Some(e) => Result::Abort(e),
+                    None => Result::Complete(_i, acc),
+                }
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $operand:ident, $operator:ident, $combine:expr ) => {{
+        use $crate::run;
+        $crate::left_assoc!($i, run!($operand), run!($operator), $combine)
+    }};
+
+    ($i:expr, $operand:ident!( $( $args:tt )* ), $operator:ident, $combine:expr ) => {{
+        use $crate::run;
+        $crate::left_assoc!($i, $operand!($($args)*), run!($operator), $combine)
+    }};
+
+    ($i:expr, $operand:ident, $operator:ident!( $( $args:tt )* ), $combine:expr ) => {{
+        use $crate::run;
+        $crate::left_assoc!($i, run!($operand), $operator!($($args)*), $combine)
+    }};
+}
+
+/// The associativity of a binary operator for `pratt!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a op b op c` parses as `(a op b) op c`.
+    Left,
+    /// `a op b op c` parses as `a op (b op c)`.
+    Right,
+}
+
+/// Parses an expression of operands and binary operators by precedence
+/// climbing. Only operators with a precedence of at least `min_prec` are
+/// consumed.
+///
+/// The operator parser returns the index, precedence, and associativity of
+/// the operator it matched and `combine` is called with that index to join
+/// the left and right operands.
+///
+/// The `pratt!` macro provides syntactic sugar for using this combinator.
+pub fn pratt<I, O, P, M, C>(
+    i: I,
+    min_prec: usize,
+    operand: &P,
+    operator: &M,
+    combine: &C,
+) -> Result<I, O>
+where
+    I: InputIter,
+    P: Fn(I) -> Result<I, O>,
+    M: Fn(I) -> Result<I, (usize, usize, Assoc)>,
+    C: Fn(usize, O, O) -> O,
+{
+    let (mut i, mut lhs) = match operand(i) {
+        Result::Complete(i, o) => (i, o),
+        Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+        Result::Fail(e) => return Result::Fail(e),
+        Result::Abort(e) => return Result::Abort(e),
+    };
+    loop {
+        let (after_op, idx, prec, assoc) = match operator(i.clone()) {
+            Result::Complete(after_op, (idx, prec, assoc)) => (after_op, idx, prec, assoc),
+            Result::Abort(e) => return Result::Abort(e),
+            Result::Incomplete(_) | Result::Fail(_) => break,
+        };
+        if prec < min_prec {
+            break;
+        }
+        let next_min = match assoc {
+            Assoc::Left => prec + 1,
+            Assoc::Right => prec,
+        };
+        match pratt(after_op, next_min, operand, operator, combine) {
+            Result::Complete(after_rhs, rhs) => {
+                lhs = combine(idx, lhs, rhs);
+                i = after_rhs;
+            }
+            Result::Abort(e) => return Result::Abort(e),
+            // An operator without a right operand isn't part of the expression.
+            Result::Incomplete(_) | Result::Fail(_) => break,
+        }
+    }
+    Result::Complete(i, lhs)
+}
+
+/// Parses an expression of operands and binary operators using a table of
+/// operators with their precedence and associativity.
+///
+/// Each entry in the table is an operator rule followed by a tuple of its
+/// precedence, its `Assoc`, and a function combining the left and right
+/// operands. Higher precedences bind tighter.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::{signed_integer, Assoc};
+/// # fn main() {
+/// let iter = iter::StrIter::new("1+2*3");
+/// let result = pratt!(iter, signed_integer, {
+///     text_token!("+") => (1, Assoc::Left, |l, r| l + r),
+///     text_token!("*") => (2, Assoc::Left, |l, r| l * r),
+/// });
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, 7);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! pratt {
+    ($i:expr, $operand:ident!( $( $operand_args:tt )* ), { $( $op:ident!( $( $op_args:tt )* ) => ( $prec:expr, $assoc:expr, $combine:expr ) ),+ $(,)? }) => {{
+        use $crate::{Error, Result};
+        $crate::combinators::pratt(
+            $i,
+            0,
+            &|i| $operand!(i, $($operand_args)*),
+            &|i| {
+                let mut idx = 0;
+                $(
+                    match $op!(i.clone(), $($op_args)*) {
+                        Result::Complete(i, _) => return Result::Complete(i, (idx, $prec, $assoc)),
+                        Result::Abort(e) => return Result::Abort(e),
+                        Result::Incomplete(_) | Result::Fail(_) => {}
+                    }
+                    #[allow(unused_assignments)]
+                    {
+                        idx += 1;
+                    }
+                )+
+                Result::Fail(Error::new("Expected an operator", Box::new(i)))
+            },
+            &|idx, l, r| {
+                let mut _n = 0;
+                $(
+                    if idx == _n {
+                        return ($combine)(l, r);
+                    }
+                    _n += 1;
+                )+
+                unreachable!()
+            },
+        )
+    }};
+
+    ($i:expr, $operand:ident, { $( $ops:tt )* }) => {{
+        use $crate::run;
+        $crate::pratt!($i, run!($operand), { $( $ops )* })
+    }};
+}
+
+/// Parses one or more items until a terminator matches. Returns a tuple of the
+/// list of items and the terminators output.
+///
+/// Fails if the terminator matches before any items have been parsed.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # fn main() {
+/// let iter = iter::StrIter::new("ab]");
+/// let result = many1_until!(iter, ascii_alpha, text_token!("]"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, (items, term)) = result {
+///     assert_eq!(vec![b'a', b'b'], items);
+///     assert_eq!("]", term);
+///     assert_eq!(i.get_offset(), 3);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! many1_until {
+    ($i:expr, $item_rule:ident!( $( $item_args:tt )* ), $term_rule:ident!( $( $term_args:tt )* ) ) => {{
+        use $crate::{Error, Result};
+        let mut _i = $i.clone();
+        let mut list = Vec::new();
+        (|| {
+            loop {
+                match $term_rule!(_i.clone(), $($term_args)*) {
+                    Result::Complete(i, term) => {
+                        if list.is_empty() {
+                            return Result::Fail(Error::new(
+                                "Expected at least one item before the terminator".to_string(),
+                                Box::new(_i.clone()),
+                            ));
+                        }
+                        return Result::Complete(i, (list, term));
+                    }
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    Result::Fail(_) => {
+                        // noop
+                    }
+                }
+                match $item_rule!(_i.clone(), $($item_args)*) {
+                    Result::Complete(i, item) => {
+                        list.push(item);
+                        _i = i;
+                    }
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    Result::Fail(e) => return Result::Fail(e),
+                }
+            }
+        })()
+    }};
+
+    ($i:expr, $item_rule:ident, $term_rule:ident ) => {{
+        use $crate::run;
+        $crate::many1_until!($i, run!($item_rule), run!($term_rule))
+    }};
+
+    ($i:expr, $item_rule:ident!( $( $args:tt )* ), $term_rule:ident ) => {{
+        use $crate::run;
+        $crate::many1_until!($i, $item_rule!($($args)*), run!($term_rule))
+    }};
+
+    ($i:expr, $item_rule:ident, $term_rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::many1_until!($i, run!($item_rule), $term_rule!($($args)*))
+    }};
+}
+
+/// Convenience macro for looking for a specific text token in a byte input stream.
+///
+/// The output is the matched span borrowed from the input. Inputs whose span
+/// can't be borrowed as text fall back to the token itself. Generic parsers
+/// need a `TextSpan` bound on their input to use it.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// use std::convert::From;
+/// # fn main() {
+/// let iter: iter::SliceIter<u8> = "foo bar".into();
+/// let tok = text_token!(iter, "foo");
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 3);
+///     assert_eq!(o, "foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! text_token {
+    ($i:expr, $e:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::{Error, Offsetable, Result, SpanRange, TextSpan};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        let mut count = 0;
+        for expected in $e.bytes() {
+            let item = match _i.next() {
+                Some(item) => item,
+                None => break,
+            };
+            if item == &expected {
+                count += 1;
+            }
+        }
+        if count == $e.len() {
+            let range = SpanRange::Range(start_offset.._i.get_offset());
+            let matched = $i.text_span(range).unwrap_or($e);
+            Result::Complete(_i.clone(), matched)
+        } else {
+            Result::Fail(Error::new(
+                format!("Expected {} but didn't get it.", $e),
+                Box::new($i.clone()),
+            ))
+        }
+    }};
+}
+
+/// Looks for a specific byte string token in a byte input stream and returns
+/// the matched span of the input rather than the token itself.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::SliceIter::new("foo bar".as_bytes());
+/// let tok = text_token_bytes!(iter, b"foo");
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 3);
+///     assert_eq!(o, b"foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! text_token_bytes {
+    ($i:expr, $e:expr) => {{
+        use $crate::{Error, Offsetable, Result, Span, SpanRange};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        let mut count = 0;
+        for expected in $e.iter() {
+            let item = match _i.next() {
+                Some(item) => item,
+                None => break,
+            };
+            if item != expected {
+                break;
+            }
+            count += 1;
+        }
+        if count == $e.len() {
+            let range = SpanRange::Range(start_offset.._i.get_offset());
+            Result::Complete(_i.clone(), $i.span(range))
+        } else {
+            Result::Fail(Error::new(
+                format!(
+                    "Expected {} but didn't get it.",
+                    String::from_utf8_lossy($e)
+                ),
+                Box::new($i.clone()),
+            ))
+        }
+    }};
+}
+
+/// Matches a keyword in a byte input stream only if it is not immediately
+/// followed by an identifier character (`_` or ascii alphanumeric).
+///
+/// This prevents `keyword!("in")` from matching the start of `integer`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("in x");
+/// assert!(keyword!(iter, "in").is_complete());
+/// let iter = iter::StrIter::new("integer");
+/// assert!(keyword!(iter, "in").is_fail());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! keyword {
+    ($i:expr, $kw:expr) => {{
+        use $crate::{Error, Result};
+        match $crate::text_token!($i, $kw) {
+            Result::Complete(i, o) => {
+                let mut _i = i.clone();
+                match _i.next() {
+                    Some(b) if (*b as char).is_ascii_alphanumeric() || *b == b'_' => {
+                        Result::Fail(Error::new(
+                            format!("Expected keyword {} but got an identifier", $kw),
+                            Box::new($i.clone()),
+                        ))
+                    }
+                    _ => Result::Complete(i, o),
+                }
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+}
+
+/// Matches a single item from the input if the predicate returns true for it.
+///
+/// The predicate is handed a clone of the item. For byte inputs that is a `&u8`
+/// and for a `CharIter` it is a `char`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("xy");
+/// let tok = satisfy!(iter, |b: &u8| *b == b'x');
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 1);
+///     assert_eq!(*o, b'x');
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! satisfy {
+    ($i:expr, $pred:expr) => {{
+        use $crate::Error;
+        use $crate::InputIter;
+        use $crate::Result;
+        let mut _i = $i.clone();
+        match _i.next() {
+            Some(item) => {
+                if ($pred)(Clone::clone(&item)) {
+                    Result::Complete(_i, item)
+                } else {
+                    Result::Fail(Error::new(
+                        "Item did not satisfy the predicate".to_string(),
+                        Box::new($i.clone()),
+                    ))
+                }
+            }
+            None if $i.needs_more() => Result::Incomplete($i.clone()),
+            None => Result::Fail(Error::new(
+                "Unexpected End Of Input".to_string(),
+                Box::new($i.clone()),
+            )),
+        }
+    }};
+}
+
+/// Matches a single byte from the input if it is a member of the `ByteClass`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// use abortable_parser::classes::ByteClass;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let digits = ByteClass::from_ranges(&[b'0'..=b'9']);
+/// let iter = iter::StrIter::new("7x");
+/// let tok = satisfy_class!(iter, digits);
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 1);
+///     assert_eq!(o, b'7');
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! satisfy_class {
+    ($i:expr, $class:expr) => {{
+        use $crate::Error;
+        use $crate::InputIter;
+        use $crate::Result;
+        let mut _i = $i.clone();
+        match _i.next() {
+            Some(item) => {
+                if $class.matches(*item) {
+                    Result::Complete(_i, *item)
+                } else {
+                    Result::Fail(Error::new(
+                        "Byte is not in the class".to_string(),
+                        Box::new($i.clone()),
+                    ))
+                }
+            }
+            None if $i.needs_more() => Result::Incomplete($i.clone()),
+            None => Result::Fail(Error::new(
+                "Unexpected End Of Input".to_string(),
+                Box::new($i.clone()),
+            )),
+        }
+    }};
+}
+
+/// Counts the leading items that satisfy the predicate without consuming them.
+///
+/// Returns Incomplete if the input runs out while more may still arrive.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("  x");
+/// let result = peek_count!(iter, |b: &u8| *b == b' ');
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, count) = result {
+///     assert_eq!(i.get_offset(), 0);
+///     assert_eq!(count, 2);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! peek_count {
+    ($i:expr, $pred:expr) => {{
+        use $crate::{InputIter, Result};
+        let mut _i = $i.clone();
+        let mut count: usize = 0;
+        loop {
+            match _i.next() {
+                Some(item) => {
+                    if !($pred)(item) {
+                        break Result::Complete($i, count);
+                    }
+                    count += 1;
+                }
+                None if _i.needs_more() => break Result::Incomplete(_i),
+                None => break Result::Complete($i, count),
+            }
+        }
+    }};
+}
+
+/// Matches a single char from an input of chars if it is one of the chars in the set.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::CharIter::new("λx");
+/// let tok = one_of_char!(iter, "αβλ");
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 2);
+///     assert_eq!(o, 'λ');
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! one_of_char {
+    ($i:expr, $set:expr) => {{
+        use $crate::Error;
+        use $crate::InputIter;
+        use $crate::Result;
+        let mut _i = $i.clone();
+        match _i.next() {
+            Some(c) => {
+                if $set.contains(c) {
+                    Result::Complete(_i, c)
+                } else {
+                    Result::Fail(Error::new(
+                        format!("Expected one of {} but got {}", $set, c),
+                        Box::new($i.clone()),
+                    ))
+                }
+            }
+            None if $i.needs_more() => Result::Incomplete($i.clone()),
+            None => Result::Fail(Error::new(
+                "Unexpected End Of Input".to_string(),
+                Box::new($i.clone()),
+            )),
+        }
+    }};
+}
+
+/// Matches a quoted string with escapes in a byte input stream and returns the
+/// unescaped `String`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new(r#"'it\'s'"#);
+/// let tok = quoted_string!(iter, b'\'', b'\\');
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(_, o) = tok {
+///     assert_eq!(o, "it's");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! quoted_string {
+    ($i:expr, $quote:expr, $escape:expr) => {
+        $crate::combinators::quoted_string($i, $quote, $escape)
+    };
+}
+
+/// Matches balanced open and close bytes in a byte input stream and returns the
+/// span between the outermost pair.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Offsetable, Result};
+/// # fn main() {
+/// let iter = iter::StrIter::new("{a{b}c}d");
+/// let tok = between_balanced!(iter, b'{', b'}');
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(o, "a{b}c");
+///     assert_eq!(i.get_offset(), 7);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! between_balanced {
+    ($i:expr, $open:expr, $close:expr) => {
+        $crate::combinators::between_balanced($i, $open, $close)
+    };
+}
+
+/// Consumes an input until it reaches a term that the contained rule matches.
+/// It does not consume the subrule.
+///
+/// If the term never matches then returns incomplete.
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// use std::convert::From;
+/// # fn main() {
+/// let iter: iter::SliceIter<u8> = "foo;".into();
+/// let tok = until!(iter, text_token!(";"));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 3);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! until {
+    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Result, Offsetable, Span};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        (|| {
+            loop {
+                match $rule!(_i.clone(), $($args)*) {
+                    Result::Complete(_, _) => {
+                        let range = start_offset.._i.get_offset();
+                        return Result::Complete(_i, $i.span(range));
+                    },
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    Result::Fail(_) => {
+                        // noop
+                    }
+                }
+                if let None = _i.next() {
+                    return Result::Incomplete(_i.clone());
+                }
+            }
+        })()
+    }};
+
+    ($i:expr, $rule:ident) => {{
+        use $crate::run;
+        $crate::until!($i, run!($rule))
+    }};
+}
+
+/// Consumes an input until it reaches a term that the contained rule matches
+/// and returns the number of items consumed. It does not consume the subrule.
+///
+/// If the term never matches then returns incomplete.
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::CharIter::new("λx;");
+/// let result = count_until!(iter, one_of_char!(";"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, count) = result {
+///     assert_eq!(count, 2);
+///     assert_eq!(i.get_offset(), 3);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! count_until {
+    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Result;
+        let mut _i = $i.clone();
+        let mut count = 0;
+        (|| {
+            loop {
+                match $rule!(_i.clone(), $($args)*) {
+                    Result::Complete(_, _) => return Result::Complete(_i, count),
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                    Result::Fail(_) => {
+                        // noop
+                    }
+                }
+                if let None = _i.next() {
+                    return Result::Incomplete(_i.clone());
+                }
+                count += 1;
+            }
+        })()
+    }};
+
+    ($i:expr, $rule:ident) => {{
+        use $crate::run;
+        $crate::count_until!($i, run!($rule))
+    }};
+}
+
+/// Consumes a line of input up to and including the next newline. Returns the
+/// line as a span without the newline.
+///
+/// `take_line!($i)` treats the end of input as a valid line terminator so a
+/// final unterminated line still matches. `take_line!($i, require_newline)`
+/// returns Incomplete instead if the input ends before a newline. Both return
+/// Incomplete if there is no input left at all.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("one\ntwo");
+/// let result = take_line!(iter);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(o, "one");
+///     assert_eq!(i.get_offset(), 4);
+///     assert!(take_line!(i, require_newline).is_incomplete());
+///     assert!(take_line!(i).is_complete());
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! take_line {
+    ($i:expr) => {
+        $crate::take_line!(__impl $i, false)
+    };
+
+    ($i:expr,) => {
+        $crate::take_line!(__impl $i, false)
+    };
+
+    ($i:expr, require_newline) => {
+        $crate::take_line!(__impl $i, true)
+    };
+
+    (__impl $i:expr, $require_newline:expr) => {{
+        use $crate::{Offsetable, Result, Span, SpanRange};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        let mut end_offset = start_offset;
+        let mut terminated = false;
+        while let Some(b) = _i.next() {
+            if *b == b'\n' {
+                terminated = true;
+                break;
+            }
+            end_offset = _i.get_offset();
+        }
+        if !terminated && ($require_newline || end_offset == start_offset) {
+            Result::Incomplete(_i.clone())
+        } else {
+            let range = SpanRange::Range(start_offset..end_offset);
+            Result::Complete(_i.clone(), $i.span(range))
+        }
+    }};
+}
+
+/// Consumes runs matched by the normal rule interleaved with escape sequences
+/// and returns the raw consumed input as a slice.
+///
+/// An escape sequence is the control byte followed by something the escapable
+/// rule matches. No unescaping is performed so you are free to do your own pass
+/// over the result. Stops at the first input that is neither a normal run nor an
+/// escape sequence.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # fn main() {
+/// let iter = iter::StrIter::new(r#"a\"b""#);
+/// let tok = escaped!(iter, ascii_alpha, b'\\', text_token!("\""));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(o, r#"a\"b"#);
+///     assert_eq!(i.get_offset(), 4);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! escaped {
+    (__impl $i:expr, $normal:ident!( $( $normal_args:tt )* ), $control:expr, $escapable:ident!( $( $escapable_args:tt )* ) ) => {{
+        use $crate::{Result, Offsetable, Span, SpanRange};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        (|| {
+            loop {
+                let run_start = _i.get_offset();
+                match $normal!(_i.clone(), $($normal_args)*) {
+                    Result::Complete(i, _) => {
+                        if i.get_offset() > run_start {
+                            _i = i;
+                            continue;
+                        }
+                    }
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(_) | Result::Fail(_) => {
+                        // noop
+                    }
+                }
+                let mut escape = _i.clone();
+                match escape.next() {
+                    Some(b) if *b == $control => {
+                        match $escapable!(escape, $($escapable_args)*) {
+                            Result::Complete(i, _) => _i = i,
+                            Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                            Result::Fail(e) => return Result::Fail(e),
+                            Result::Abort(e) => return Result::Abort(e),
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            let range = SpanRange::Range(start_offset.._i.get_offset());
+            Result::Complete(_i, $i.span(range))
+        })()
+    }};
+
+    ($i:expr, $normal:ident!( $( $normal_args:tt )* ), $control:expr, $escapable:ident!( $( $escapable_args:tt )* ) ) => {
+        $crate::escaped!(__impl $i, $normal!($($normal_args)*), $control, $escapable!($($escapable_args)*))
+    };
+
+    ($i:expr, $normal:ident, $control:expr, $escapable:ident ) => {{
+        use $crate::run;
+        $crate::escaped!(__impl $i, run!($normal), $control, run!($escapable))
+    }};
+
+    ($i:expr, $normal:ident!( $( $args:tt )* ), $control:expr, $escapable:ident ) => {{
+        use $crate::run;
+        $crate::escaped!(__impl $i, $normal!($($args)*), $control, run!($escapable))
+    }};
+
+    ($i:expr, $normal:ident, $control:expr, $escapable:ident!( $( $args:tt )* ) ) => {{
+        use $crate::run;
+        $crate::escaped!(__impl $i, run!($normal), $control, $escapable!($($args)*))
+    }};
+}
+
+/// Skips any run of whitespace and comments returning `()`.
+///
+/// Comment styles are listed after the input. A `line: "//"` style skips from
+/// the start token to the end of the line and a `block: ("/*", "*/")` style
+/// skips from the open token to the close token. You can list as many styles as
+/// your grammar requires. An unterminated block comment is an Abort.
+///
+/// Define it once with `make_fn!` and reuse it wherever insignificant input is
+/// allowed.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter::StrIter;
+/// # use abortable_parser::{Result, Offsetable};
+/// make_fn!(skip<StrIter, ()>,
+///     skip_rule!(line: "#", block: ("/*", "*/"))
+/// );
+///
+/// # fn main() {
+/// let iter = StrIter::new(" # comment\n/* block */x");
+/// let result = skip(iter);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, _) = result {
+///     assert_eq!(i.get_offset(), 22);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! skip_rule {
+    (__style $i:expr, line, $start:expr) => {{
+        use $crate::Result;
+        match $crate::text_token!($i, $start) {
+            Result::Complete(mut _i, _) => {
+                loop {
+                    let __i = _i.clone();
+                    match _i.next() {
+                        Some(b) if *b == b'\n' => break,
+                        Some(_) => (),
+                        None => {
+                            _i = __i;
+                            break;
+                        }
+                    }
+                }
+                Result::Complete(_i, ())
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    (__style $i:expr, block, ($open:expr, $close:expr)) => {{
+        use $crate::{Error, Result};
+        match $crate::text_token!($i, $open) {
+            Result::Complete(mut _i, _) => loop {
+                if let Result::Complete(i, _) = $crate::text_token!(_i.clone(), $close) {
+                    break Result::Complete(i, ());
+                }
+                if _i.next().is_none() {
+                    break Result::Abort(Error::new(
+                        "Unterminated block comment".to_string(),
+                        Box::new($i.clone()),
+                    ));
+                }
+            },
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $( $kind:ident : $spec:tt ),* $(,)?) => {{
+        use $crate::Result;
+        let mut _i = $i.clone();
+        (|| {
+            loop {
+                let mut __i = _i.clone();
+                if let Some(b) = __i.next() {
+                    if (*b as char).is_whitespace() {
+                        _i = __i;
+                        continue;
+                    }
+                }
+                $(
+                    match $crate::skip_rule!(__style _i.clone(), $kind, $spec) {
+                        Result::Complete(i, _) => {
+                            _i = i;
+                            continue;
+                        }
+                        Result::Abort(e) => return Result::Abort(e),
+                        Result::Incomplete(ctx) => return Result::Incomplete(ctx),
+                        Result::Fail(_) => {
+                            // noop
+                        }
+                    }
+                )*
+                return Result::Complete(_i, ());
+            }
+        })()
+    }};
+}
+
+/// Runs a sub parser over a `FurthestTracker` and on a Fail or Abort wraps the
+/// error in one located at the furthest offset the parser read from.
+///
+/// The tracker is reset to the current offset before the sub parser runs.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter::{FurthestTracker, StrIter};
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = FurthestTracker::new(StrIter::new("foo"));
+/// let result = track_furthest!(iter, either!(
+///     do_each!(_ => text_token!("f"), _ => text_token!("x"), ()),
+///     do_each!(_ => text_token!("y"), ())
+/// ));
+/// # assert!(result.is_fail());
+/// if let Result::Fail(e) = result {
+///     assert_eq!(e.get_offset(), 1);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_furthest {
+    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Error, Result, Seekable};
+        $i.reset_furthest();
+        let tracker = $i.clone();
+        match $rule!($i, $($args)*) {
+            Result::Complete(i, o) => Result::Complete(i, o),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => {
+                let furthest = tracker.furthest_offset();
+                let mut ctx = e.get_context().clone();
+                ctx.seek(furthest);
+                Result::Fail(Error::caused_by(
+                    format!("Parsing failed after reaching offset {}", furthest),
+                    Box::new(e),
+                    Box::new(ctx),
+                ))
+            }
+            Result::Abort(e) => {
+                let furthest = tracker.furthest_offset();
+                let mut ctx = e.get_context().clone();
+                ctx.seek(furthest);
+                Result::Abort(Error::caused_by(
+                    format!("Parsing failed after reaching offset {}", furthest),
+                    Box::new(e),
+                    Box::new(ctx),
+                ))
+            }
+        }
+    }};
+
+    ($i:expr, $rule:ident) => {{
+        use $crate::run;
+        $crate::track_furthest!($i, run!($rule))
+    }};
+}
+
+/// Discards the output of a combinator rule when it completes and just returns `()`.
+/// Leaves Failures, Aborts, and Incompletes untouched.
+#[macro_export]
+macro_rules! discard {
+    ($i:expr, $rule:ident) => {{
+        use $crate::run;
+        $crate::discard!($i, run!($rule))
+    }};
+
+    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::Result;
+        match $rule!($i, $($args)*) {
+            Result::Complete(i, _) => Result::Complete(i, ()),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+}
+
+/// Runs a sub parser, requires that it consumed all of the input, and then
+/// validates the output with a function returning `std::result::Result<(), String>`.
+///
+/// Trailing input or a validation error results in a `Result::Abort`. The
+/// validation function is passed a reference to the output.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = verify_full!(iter, text_token!("foo"), |o: &&str| {
+///     if o.len() == 3 { Ok(()) } else { Err("Expected 3 bytes".to_string()) }
+/// });
+/// # assert!(result.is_complete());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! verify_full {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $validate:expr) => {{
+        use $crate::{Error, Result};
+        let _i = $i.clone();
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => match $crate::combinators::eoi(i.clone()) {
+                Result::Complete(_, _) => match ($validate)(&o) {
+                    Ok(()) => Result::Complete(i, o),
+                    Err(msg) => Result::Abort(Error::new(msg, Box::new(_i.clone()))),
+                },
+                Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                _ => Result::Abort(Error::new(
+                    "Unexpected trailing input".to_string(),
+                    Box::new(i.clone()),
+                )),
+            },
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident, $validate:expr) => {{
+        use $crate::run;
+        $crate::verify_full!($i, run!($f), $validate)
+    }};
+}
+
+/// Runs a sub parser and then requires that it consumed the entire input.
+///
+/// Leftover input results in a `Result::Fail` noting the offset it starts at.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foobar");
+/// let result = parse_all!(iter, text_token!("foo"));
+/// # assert!(result.is_fail());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! parse_all {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Error, Offsetable, Result};
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => match $crate::combinators::eoi(i.clone()) {
+                Result::Complete(_, _) => Result::Complete(i, o),
+                Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                _ => Result::Fail(Error::new(
+                    format!("Unconsumed input at offset {}", i.get_offset()),
+                    Box::new(i.clone()),
+                )),
+            },
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::parse_all!($i, run!($f))
+    }};
+}
+
+/// Runs a sub parser and returns a `Result::Abort` with the message if the
+/// predicate returns true for its output.
+///
+/// This is useful for stopping the whole parse on input that is syntactically
+/// valid but can never be semantically valid. The predicate is passed a
+/// reference to the output.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = abort_if!(iter, text_token!("foo"), |o: &&str| o.is_empty(), "Empty token");
+/// # assert!(result.is_complete());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! abort_if {
+    ($i:expr, $f:ident!( $( $args:tt )* ), $pred:expr, $msg:expr) => {{
+        use $crate::{Error, Result};
+        let _i = $i.clone();
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => {
+                if ($pred)(&o) {
+                    Result::Abort(Error::new($msg, Box::new(_i.clone())))
+                } else {
+                    Result::Complete(i, o)
+                }
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident, $pred:expr, $msg:expr) => {{
+        use $crate::run;
+        $crate::abort_if!($i, run!($f), $pred, $msg)
+    }};
+}
+
+/// Matches and returns any ascii charactar whitespace byte.
+pub fn ascii_ws<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
+    match i.next() {
+        Some(b) => {
+            if (*b as char).is_whitespace() {
+                Result::Complete(i, *b)
+            } else {
+                Result::Fail(Error::new(
+                    "Not whitespace".to_string(),
+                    Box::new(i.clone()),
+                ))
+            }
+        }
+        None if i.needs_more() => Result::Incomplete(i.clone()),
+        None => Result::Fail(Error::new(
+            "Unexpected End Of Input".to_string(),
+            Box::new(i.clone()),
+        )),
+    }
+}
+
+/// Matches zero or more ascii whitespace bytes and returns the consumed span.
+///
+/// Always completes, returning an empty span if there is no whitespace.
+pub fn multispace0<'a, I, O>(i: I) -> Result<I, O>
+where
+    I: InputIter<Item = &'a u8> + Span<O>,
+{
+    let start_offset = i.get_offset();
+    let mut _i = i.clone();
+    let mut end = i.clone();
+    while let Some(b) = _i.next() {
+        if !(*b as char).is_whitespace() {
+            break;
+        }
+        end = _i.clone();
+    }
+    let ws = i.span(SpanRange::Range(start_offset..end.get_offset()));
+    Result::Complete(end, ws)
+}
+
+/// Matches one or more ascii whitespace bytes and returns the consumed span.
+///
+/// Fails if the input doesn't start with whitespace.
+pub fn multispace1<'a, I, O>(i: I) -> Result<I, O>
+where
+    I: InputIter<Item = &'a u8> + Span<O>,
+{
+    match multispace0(i.clone()) {
+        Result::Complete(_i, ws) => {
+            if _i.get_offset() == i.get_offset() {
+                Result::Fail(Error::new("Not whitespace".to_string(), Box::new(i)))
+            } else {
+                Result::Complete(_i, ws)
+            }
+        }
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
+/// Matches the end of input for any InputIter.
+/// Returns `()` for any match and `Result::Incomplete` if more input may
+/// still arrive.
+pub fn eoi<I: InputIter>(i: I) -> Result<I, ()> {
+    let mut _i = i.clone();
+    match _i.next() {
+        Some(_) => Result::Fail(Error::new(
+            "Expected End Of Input".to_string(),
+            Box::new(i.clone()),
+        )),
+        None if i.needs_more() => Result::Incomplete(i),
+        None => Result::Complete(i, ()),
+    }
+}
+
+/// Parses a dotted key path with bracketed indexes into a `Vec<PathSegment>`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # use abortable_parser::combinators::PathSegment;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo[1].bar");
+/// let result = key_path!(iter);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, path) = result {
+///     assert_eq!(path, vec![
+///         PathSegment::Key("foo"),
+///         PathSegment::Index(1),
+///         PathSegment::Key("bar"),
+///     ]);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! key_path {
+    ($i:expr) => {
+        $crate::combinators::key_path($i)
+    };
+}
+
+/// Constructs a function named $name that takes an input of type $i and produces an output
+/// of type $o.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// make_fn!(myrule<StrIter, &str>,
+///     text_token!("token")
+/// );
+/// ```
+///
+/// You can also specify that the function is public if so desired.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// make_fn!(pub otherrule<StrIter, &str>,
+///     text_token!("other")
+/// );
+/// ```
+///
+/// Lifetimes can be named before the input type so that the output can borrow
+/// from the input.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// make_fn!(spanrule<'a, StrIter<'a>, &'a str>,
+///     until!(text_token!(";"))
+/// );
+/// ```
+///
+/// A function can be generic over its input type by naming a type parameter
+/// along with any lifetimes and adding a where clause.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::{InputIter, TextSpan};
+/// make_fn!(genericrule<'a, I, &'a str> where I: InputIter<Item = &'a u8> + TextSpan<'a>,
+///     text_token!("generic")
+/// );
+/// ```
+#[macro_export]
+macro_rules! make_fn {
+    // Generic function with the where clause collected.
+    (__generic [ $( $vis:tt )* ] $name:ident [ $( $lt:lifetime ),* ] $i:ident [ $o:ty ] [ $( $where:tt )* ] , $rule:ident!($( $body:tt )* )) => {
+        $( $vis )* fn $name<$( $lt, )* $i>(i: $i) -> $crate::Result<$i, $o>
+        where
+            $( $where )*
+        {
+            $rule!(i, $($body)*)
+        }
+    };
+
+    (__generic [ $( $vis:tt )* ] $name:ident [ $( $lt:lifetime ),* ] $i:ident [ $o:ty ] [ $( $where:tt )* ] , $rule:ident) => {
+        $( $vis )* fn $name<$( $lt, )* $i>(i: $i) -> $crate::Result<$i, $o>
+        where
+            $( $where )*
+        {
+            $rule(i)
+        }
+    };
+
+    // Collect the where clause one token at a time until only the rule is left.
+    (__generic [ $( $vis:tt )* ] $name:ident [ $( $lt:lifetime ),* ] $i:ident [ $o:ty ] [ $( $where:tt )* ] $next:tt $( $rest:tt )*) => {
+        $crate::make_fn!(__generic [ $( $vis )* ] $name [ $( $lt ),* ] $i [ $o ] [ $( $where )* $next ] $( $rest )*);
+    };
+
+    ($name:ident<$( $lt:lifetime, )* $i:ident, $o:ty> where $( $rest:tt )+) => {
+        $crate::make_fn!(__generic [] $name [ $( $lt ),* ] $i [ $o ] [] $( $rest )+);
+    };
+
+    (pub $name:ident<$( $lt:lifetime, )* $i:ident, $o:ty> where $( $rest:tt )+) => {
+        $crate::make_fn!(__generic [ pub ] $name [ $( $lt ),* ] $i [ $o ] [] $( $rest )+);
+    };
+
+    ($name:ident<$( $lt:lifetime, )+ $i:ty, $o:ty>, $rule:ident!($( $body:tt )* )) => {
+        fn $name<$( $lt ),+>(i: $i) -> $crate::Result<$i, $o> {
+            $rule!(i, $($body)*)
+        }
+    };
+
+    (pub $name:ident<$( $lt:lifetime, )+ $i:ty, $o:ty>, $rule:ident!($( $body:tt )* )) => {
+        pub fn $name<$( $lt ),+>(i: $i) -> $crate::Result<$i, $o> {
+            $rule!(i, $($body)*)
+        }
+    };
+
+    ($name:ident<$( $lt:lifetime, )+ $i:ty, $o:ty>, $rule:ident) => {
+        fn $name<$( $lt ),+>(i: $i) -> $crate::Result<$i, $o> {
+            $rule(i)
+        }
+    };
+
+    (pub $name:ident<$( $lt:lifetime, )+ $i:ty, $o:ty>, $rule:ident) => {
+        pub fn $name<$( $lt ),+>(i: $i) -> $crate::Result<$i, $o> {
+            $rule(i)
+        }
+    };
+
+    ($name:ident<$i:ty, $o:ty>, $rule:ident!($( $body:tt )* )) => {
+        fn $name(i: $i) -> $crate::Result<$i, $o> {
+            $rule!(i, $($body)*)
+        }
+    };
+
+    (pub $name:ident<$i:ty, $o:ty>, $rule:ident!($( $body:tt )* )) => {
+        pub fn $name(i: $i) -> $crate::Result<$i, $o> {
+            $rule!(i, $($body)*)
+        }
+    };
+
+    ($name:ident<$i:ty, $o:ty>, $rule:ident) => {
+        use $crate::run
+        $crate::make_fn!($name<$i, $o>, run!($rule));
+    };
+
+    (pub $name:ident<$i:ty, $o:ty>, $rule:ident) => {
+        use $crate::run
+        $crate::make_fn!(pub $name<$i, $o>, run!($rule));
+    };
+}
+
+/// Helper macro that returns the input without consuming it.
+///
+/// Useful when you need to get the input and use it to retrieve
+/// positional information like offset or line and column.
+#[macro_export]
+macro_rules! input {
+    ($i:expr) => {
+        $crate::input!($i,)
+    };
+
+    ($i:expr,) => {{
+        let _i = $i.clone();
+        $crate::Result::Complete($i, _i)
+    }};
+}
+
+/// Runs a sub parser and returns its output along with the span of the input
+/// that it consumed.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo;");
+/// let result = with_span!(iter, repeat!(ascii_alpha));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, (o, span)) = result {
+///     assert_eq!(o.len(), 3);
+///     assert_eq!(span, "foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! with_span {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Offsetable, Result, Span, SpanRange};
+        let _i = $i.clone();
+        let start_offset = _i.get_offset();
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => {
+                let span = _i.span(SpanRange::Range(start_offset..i.get_offset()));
+                Result::Complete(i, (o, span))
+            }
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::with_span!($i, run!($f))
+    }};
+}
+
+/// Runs a sub parser and returns its output along with the line and column
+/// where it started.
+///
+/// The input must implement `Positioned`.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = located!(iter, text_token!("foo"));
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, (o, pos)) = result {
+///     assert_eq!(o, "foo");
+///     assert_eq!(pos, (1, 1));
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! located {
+    ($i:expr, $f:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Positioned, Result};
+        let pos = ($i.line(), $i.column());
+        match $f!($i, $($args)*) {
+            Result::Complete(i, o) => Result::Complete(i, (o, pos)),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }};
+
+    ($i:expr, $f:ident) => {{
+        use $crate::run;
+        $crate::located!($i, run!($f))
+    }};
+}
+
+/// Helper macro that returns the number of items remaining in the input without
+/// consuming it.
+///
+/// Returns a `Result::Fail` for inputs that don't know their remaining length.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = rest_len!(iter);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, len) = result {
+///     assert_eq!(len, 3);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! rest_len {
+    ($i:expr) => {{
+        use $crate::{Error, InputIter, Result};
+        match $i.remaining_len() {
+            Some(len) => Result::Complete($i, len),
+            None => Result::Fail(Error::new(
+                "Remaining length of the input is unknown".to_string(),
+                Box::new($i.clone()),
+            )),
+        }
+    }};
 }
 
-/// Constructs a function named $name that takes an input of type $i and produces an output
-/// of type $o.
+/// Helper macro that returns the full source lines of a `StrIter` covering the
+/// range between two offsets.
+///
+/// Useful for showing the surrounding source in diagnostics.
 ///
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
-/// # use abortable_parser::iter::StrIter;
-/// make_fn!(myrule<StrIter, &str>,
-///     text_token!("token")
-/// );
+/// use abortable_parser::iter;
+/// # fn main() {
+/// let iter = iter::StrIter::new("one\ntwo three\nfour");
+/// assert_eq!(span_lines!(iter, 8, 11), "two three");
+/// # }
 /// ```
+#[macro_export]
+macro_rules! span_lines {
+    ($i:expr, $start:expr, $end:expr) => {
+        $i.span_lines($start..$end)
+    };
+}
+
+/// Helper macro that returns the span of the input between two offsets.
 ///
-/// You can also specify that the function is public if so desired.
+/// Pairs well with `input!` to capture the raw input consumed by a sub parse.
 ///
 /// ```
 /// # #[macro_use] extern crate abortable_parser;
-/// # use abortable_parser::iter::StrIter;
-/// make_fn!(pub otherrule<StrIter, &str>,
-///     text_token!("other")
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo bar");
+/// let result = do_each!(iter,
+///     start => input!(),
+///     _ => text_token!("foo"),
+///     end => input!(),
+///     (span_between!(end, start.get_offset(), end.get_offset()))
 /// );
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, "foo");
+/// }
+/// # }
 /// ```
 #[macro_export]
-macro_rules! make_fn {
-    ($name:ident<$i:ty, $o:ty>, $rule:ident!($( $body:tt )* )) => {
-        fn $name(i: $i) -> $crate::Result<$i, $o> {
-            $rule!(i, $($body)*)
-        }
-    };
+macro_rules! span_between {
+    ($i:expr, $start:expr, $end:expr) => {{
+        use $crate::{Span, SpanRange};
+        $i.span(SpanRange::Range($start..$end))
+    }};
+}
 
-    (pub $name:ident<$i:ty, $o:ty>, $rule:ident!($( $body:tt )* )) => {
-        pub fn $name(i: $i) -> $crate::Result<$i, $o> {
-            $rule!(i, $($body)*)
-        }
+/// Helper macro that returns a `Checkpoint` for the current input position
+/// without consuming anything.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let result = checkpoint!(iter);
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, cp) = result {
+///     assert_eq!(cp.get_offset(), 0);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! checkpoint {
+    ($i:expr) => {
+        $crate::checkpoint!($i,)
     };
 
-    ($name:ident<$i:ty, $o:ty>, $rule:ident) => {
-        use $crate::run
-        $crate::make_fn!($name<$i, $o>, run!($rule));
-    };
+    ($i:expr,) => {{
+        use $crate::{Checkpoint, Offsetable, Result};
+        let cp = Checkpoint::from($i.get_offset());
+        Result::Complete($i, cp)
+    }};
+}
 
-    (pub $name:ident<$i:ty, $o:ty>, $rule:ident) => {
-        use $crate::run
-        $crate::make_fn!(pub $name<$i, $o>, run!($rule));
-    };
+/// Returns the span of the input between two checkpoints without consuming
+/// anything.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::Result;
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo bar");
+/// let result = do_each!(iter,
+///     start => checkpoint!(),
+///     _ => text_token!("foo"),
+///     end => checkpoint!(),
+///     span => span_from_checkpoints!(start, end),
+///     (span)
+/// );
+/// # assert!(result.is_complete());
+/// if let Result::Complete(_, o) = result {
+///     assert_eq!(o, "foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! span_from_checkpoints {
+    ($i:expr, $start:expr, $end:expr) => {{
+        use $crate::{Offsetable, Result};
+        let span = $crate::span_between!($i, $start.get_offset(), $end.get_offset());
+        Result::Complete($i, span)
+    }};
 }
 
-/// Helper macro that returns the input without consuming it.
+/// Consumes the rest of the input and returns it as a span.
 ///
-/// Useful when you need to get the input and use it to retrieve
-/// positional information like offset or line and column.
+/// Returns an empty span if the input is already at the end.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo bar");
+/// let result = do_each!(iter,
+///     _ => text_token!("foo "),
+///     rest => rest!(),
+///     (rest)
+/// );
+/// # assert!(result.is_complete());
+/// if let Result::Complete(i, o) = result {
+///     assert_eq!(o, "bar");
+///     assert_eq!(i.get_offset(), 7);
+/// }
+/// # }
+/// ```
 #[macro_export]
-macro_rules! input {
+macro_rules! rest {
     ($i:expr) => {
-        $crate::input!($i,)
+        $crate::rest!($i,)
     };
 
     ($i:expr,) => {{
-        let _i = $i.clone();
-        $crate::Result::Complete($i, _i)
+        use $crate::{Offsetable, Result, Span, SpanRange};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        while _i.next().is_some() {}
+        let range = SpanRange::Range(start_offset.._i.get_offset());
+        Result::Complete(_i.clone(), $i.span(range))
     }};
 }
 
@@ -961,10 +4166,10 @@ macro_rules! input {
 #[macro_export]
 macro_rules! consume_all {
     ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
-        use $crate::{Result, Offsetable, Span, SpanRange};
+        use $crate::{Result, Offsetable, Span};
         let start_offset = $i.get_offset();
         let mut _i = $i.clone();
-        let pfn = || {
+        (|| {
             loop {
                 match $rule!(_i.clone(), $($args)*) {
                     Result::Complete(_, _) => {
@@ -973,7 +4178,7 @@ macro_rules! consume_all {
                     Result::Abort(e) => return Result::Abort(e),
                     Result::Incomplete(ctx) => return Result::Incomplete(ctx),
                     Result::Fail(_) => {
-                        let range = SpanRange::Range(start_offset.._i.get_offset());
+                        let range = start_offset.._i.get_offset();
                         return Result::Complete(_i, $i.span(range));
                     }
                 }
@@ -981,8 +4186,7 @@ macro_rules! consume_all {
                     return Result::Incomplete(_i.clone());
                 }
             }
-        };
-        pfn()
+        })()
     }};
 
     ($i:expr, $rule:ident) => {{
@@ -991,6 +4195,55 @@ macro_rules! consume_all {
     }}
 }
 
+/// Like `consume_all!` but treats running out of input as a successful stop and
+/// returns the input consumed so far instead of Incomplete.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// use abortable_parser::iter;
+/// # use abortable_parser::{Result, Offsetable};
+/// # fn main() {
+/// let iter = iter::StrIter::new("foo");
+/// let tok = consume_all0!(iter, not!(text_token!(";")));
+/// # assert!(tok.is_complete());
+/// if let Result::Complete(i, o) = tok {
+///     assert_eq!(i.get_offset(), 3);
+///     assert_eq!(o, "foo");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! consume_all0 {
+    ($i:expr, $rule:ident!( $( $args:tt )* ) ) => {{
+        use $crate::{Result, Offsetable, Span};
+        let start_offset = $i.get_offset();
+        let mut _i = $i.clone();
+        (|| {
+            loop {
+                match $rule!(_i.clone(), $($args)*) {
+                    Result::Complete(_, _) => {
+                        // noop
+                    },
+                    Result::Abort(e) => return Result::Abort(e),
+                    Result::Incomplete(_) | Result::Fail(_) => {
+                        let range = start_offset.._i.get_offset();
+                        return Result::Complete(_i, $i.span(range));
+                    }
+                }
+                if let None = _i.next() {
+                    let range = start_offset.._i.get_offset();
+                    return Result::Complete(_i.clone(), $i.span(range));
+                }
+            }
+        })()
+    }};
+
+    ($i:expr, $rule:ident) => {{
+        use $crate::run;
+        $crate::consume_all0!($i, run!($rule))
+    }}
+}
+
 /// ascii_digit parses a single ascii alphabetic or digit character from an InputIter of bytes.
 #[inline(always)]
 pub fn ascii_alphanumeric<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
@@ -1006,6 +4259,7 @@ pub fn ascii_alphanumeric<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I
                 ))
             }
         }
+        None if i.needs_more() => Result::Incomplete(i.clone()),
         None => Result::Fail(Error::new(
             "Unexpected End Of Input.".to_string(),
             Box::new(i.clone()),
@@ -1027,6 +4281,29 @@ pub fn ascii_digit<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
                 ))
             }
         }
+        None if i.needs_more() => Result::Incomplete(i.clone()),
+        None => Result::Fail(Error::new(
+            "Unexpected End Of Input.".to_string(),
+            Box::new(i.clone()),
+        )),
+    }
+}
+
+/// ascii_hexdigit parses a single ascii hex digit character from an InputIter of bytes.
+#[inline(always)]
+pub fn ascii_hexdigit<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
+    match i.next() {
+        Some(b) => {
+            if (*b as char).is_ascii_hexdigit() {
+                Result::Complete(i, *b)
+            } else {
+                Result::Fail(Error::new(
+                    "Not a hex digit character".to_string(),
+                    Box::new(i.clone()),
+                ))
+            }
+        }
+        None if i.needs_more() => Result::Incomplete(i.clone()),
         None => Result::Fail(Error::new(
             "Unexpected End Of Input.".to_string(),
             Box::new(i.clone()),
@@ -1048,6 +4325,7 @@ pub fn ascii_alpha<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
                 ))
             }
         }
+        None if i.needs_more() => Result::Incomplete(i.clone()),
         None => Result::Fail(Error::new(
             "Unexpected End Of Input.".to_string(),
             Box::new(i.clone()),
@@ -1055,5 +4333,294 @@ pub fn ascii_alpha<'a, I: InputIter<Item = &'a u8>>(mut i: I) -> Result<I, u8> {
     }
 }
 
+// Advances past a run of ascii digits and returns how many there were.
+fn consume_digits(i: &mut StrIter) -> usize {
+    let mut count = 0;
+    while let Some(b) = i.peek_next() {
+        if !b.is_ascii_digit() {
+            break;
+        }
+        i.next();
+        count += 1;
+    }
+    count
+}
+
+/// signed_integer parses an optionally signed run of ascii digits into an i64.
+///
+/// Fails if there are no digits and aborts if the digits overflow an i64. Any
+/// trailing input is left untouched.
+pub fn signed_integer<'a>(i: StrIter<'a>) -> Result<StrIter<'a>, i64> {
+    let mut _i = i;
+    if let Some(&b'+') | Some(&b'-') = _i.peek_next() {
+        _i.next();
+    }
+    if consume_digits(&mut _i) == 0 {
+        return Result::Fail(Error::new("Expected an integer".to_string(), Box::new(i)));
+    }
+    let text = i.span(SpanRange::Range(i.get_offset().._i.get_offset()));
+    match text.parse::<i64>() {
+        Ok(n) => Result::Complete(_i, n),
+        Err(_) => Result::Abort(Error::new(
+            format!("Integer {} does not fit in an i64", text),
+            Box::new(i),
+        )),
+    }
+}
+
+/// floating_point parses an optionally signed decimal number with an optional
+/// fractional part and an optional exponent into an f64.
+///
+/// Fails if there are no digits so it can be used inside of `either!`. Any
+/// trailing input is left untouched.
+pub fn floating_point<'a>(i: StrIter<'a>) -> Result<StrIter<'a>, f64> {
+    let mut _i = i;
+    if let Some(&b'+') | Some(&b'-') = _i.peek_next() {
+        _i.next();
+    }
+    let mut digits = consume_digits(&mut _i);
+    if let Some(&b'.') = _i.peek_next() {
+        let mut fraction = _i;
+        fraction.next();
+        let fraction_digits = consume_digits(&mut fraction);
+        if digits > 0 || fraction_digits > 0 {
+            digits += fraction_digits;
+            _i = fraction;
+        }
+    }
+    if digits == 0 {
+        return Result::Fail(Error::new(
+            "Expected a floating point number".to_string(),
+            Box::new(i),
+        ));
+    }
+    if let Some(&b'e') | Some(&b'E') = _i.peek_next() {
+        // The exponent is only consumed if it is well formed.
+        let mut exponent = _i;
+        exponent.next();
+        if let Some(&b'+') | Some(&b'-') = exponent.peek_next() {
+            exponent.next();
+        }
+        if consume_digits(&mut exponent) > 0 {
+            _i = exponent;
+        }
+    }
+    let text = i.span(SpanRange::Range(i.get_offset().._i.get_offset()));
+    match text.parse::<f64>() {
+        Ok(n) => Result::Complete(_i, n),
+        Err(_) => Result::Abort(Error::new(
+            format!("Invalid floating point number {}", text),
+            Box::new(i),
+        )),
+    }
+}
+
+/// A segment of a key path as parsed by `key_path`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment<'a> {
+    /// A dotted identifier segment.
+    Key(&'a str),
+    /// A bracketed integer index segment.
+    Index(usize),
+}
+
+fn path_key<'a, I>(i: I) -> Result<I, &'a str>
+where
+    I: InputIter<Item = &'a u8> + Span<&'a str>,
+{
+    let start_offset = i.get_offset();
+    let mut _i = i.clone();
+    let mut end = i.clone();
+    while let Some(b) = _i.next() {
+        let c = *b as char;
+        let first = end.get_offset() == start_offset;
+        if !(c.is_ascii_alphabetic() || c == '_' || (!first && c.is_ascii_digit())) {
+            break;
+        }
+        end = _i.clone();
+    }
+    if end.get_offset() == start_offset {
+        return Result::Fail(Error::new(
+            "Expected a key".to_string(),
+            Box::new(i.clone()),
+        ));
+    }
+    let key = i.span(SpanRange::Range(start_offset..end.get_offset()));
+    Result::Complete(end, key)
+}
+
+fn path_index<'a, I>(i: I) -> Result<I, usize>
+where
+    I: InputIter<Item = &'a u8> + Span<&'a str>,
+{
+    match repeat!(i.clone(), run!(ascii_digit)) {
+        Result::Complete(_i, digits) => {
+            if digits.is_empty() {
+                return Result::Fail(Error::new(
+                    "Expected an index".to_string(),
+                    Box::new(i.clone()),
+                ));
+            }
+            let text = i.span(SpanRange::Range(i.get_offset().._i.get_offset()));
+            match text.parse::<usize>() {
+                Ok(idx) => Result::Complete(_i, idx),
+                Err(_) => Result::Abort(Error::new(
+                    format!("Index {} is too large", text),
+                    Box::new(i.clone()),
+                )),
+            }
+        }
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
+/// Parses a key path like `foo.bar[2].baz` into a list of `PathSegment`s.
+///
+/// The path must start with an identifier. Once a `.` or `[` has been seen the
+/// rest of the segment is required and a malformed segment is an Abort.
+///
+/// The `key_path!` macro provides syntactic sugar for using this combinator.
+pub fn key_path<'a, I>(i: I) -> Result<I, Vec<PathSegment<'a>>>
+where
+    I: InputIter<Item = &'a u8> + Span<&'a str>,
+{
+    match path_key(i) {
+        Result::Complete(i, head) => {
+            let tail = repeat!(
+                i,
+                either!(
+                    do_each!(
+                        _ => text_token!("."),
+                        key => must!(run!(path_key)),
+                        (PathSegment::Key(key))
+                    ),
+                    do_each!(
+                        _ => text_token!("["),
+                        idx => must!(run!(path_index)),
+                        _ => must!(text_token!("]")),
+                        (PathSegment::Index(idx))
+                    )
+                )
+            );
+            match tail {
+                Result::Complete(i, tail) => {
+                    let mut path = vec![PathSegment::Key(head)];
+                    path.extend(tail);
+                    Result::Complete(i, path)
+                }
+                Result::Incomplete(ctx) => Result::Incomplete(ctx),
+                Result::Fail(e) => Result::Fail(e),
+                Result::Abort(e) => Result::Abort(e),
+            }
+        }
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
+/// Matches the open byte and consumes input up to the close byte at the same
+/// nesting depth. Returns the span between the open and close bytes.
+///
+/// Returns Fail if the input doesn't start with the open byte and Incomplete if
+/// the input ends before the delimiters are balanced.
+///
+/// The `between_balanced!` macro provides syntactic sugar for using this combinator.
+pub fn between_balanced<'a, I, O>(i: I, open: u8, close: u8) -> Result<I, O>
+where
+    I: InputIter<Item = &'a u8> + Span<O>,
+{
+    let mut _i = i.clone();
+    match _i.next() {
+        Some(b) if *b == open => (),
+        Some(_) => {
+            return Result::Fail(Error::new(
+                format!("Expected {}", open as char),
+                Box::new(i.clone()),
+            ))
+        }
+        None if i.needs_more() => return Result::Incomplete(i.clone()),
+        None => {
+            return Result::Fail(Error::new(
+                "Unexpected End Of Input".to_string(),
+                Box::new(i.clone()),
+            ))
+        }
+    }
+    let start_offset = _i.get_offset();
+    let mut depth = 1;
+    loop {
+        let end_offset = _i.get_offset();
+        match _i.next() {
+            Some(b) if *b == close => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner = i.span(SpanRange::Range(start_offset..end_offset));
+                    return Result::Complete(_i, inner);
+                }
+            }
+            Some(b) if *b == open => depth += 1,
+            Some(_) => (),
+            None => return Result::Incomplete(_i.clone()),
+        }
+    }
+}
+
+/// Parses a string delimited by the quote byte where the escape byte allows
+/// the next byte to appear literally. Returns the unescaped contents.
+///
+/// Aborts if the input ends before the closing quote.
+///
+/// The `quoted_string!` macro provides syntactic sugar for using this combinator.
+pub fn quoted_string<'a, I>(i: I, quote: u8, escape: u8) -> Result<I, String>
+where
+    I: InputIter<Item = &'a u8>,
+{
+    let mut _i = i.clone();
+    match _i.next() {
+        Some(b) if *b == quote => (),
+        Some(_) => {
+            return Result::Fail(Error::new(
+                format!("Expected {}", quote as char),
+                Box::new(i.clone()),
+            ))
+        }
+        None if i.needs_more() => return Result::Incomplete(i.clone()),
+        None => {
+            return Result::Fail(Error::new(
+                "Unexpected End Of Input".to_string(),
+                Box::new(i.clone()),
+            ))
+        }
+    }
+    let mut buf = Vec::new();
+    loop {
+        let b = match _i.next() {
+            Some(b) if *b == escape => match _i.next() {
+                Some(b) => *b,
+                None => break,
+            },
+            Some(b) if *b == quote => {
+                return match String::from_utf8(buf) {
+                    Ok(s) => Result::Complete(_i, s),
+                    Err(_) => Result::Abort(Error::new(
+                        "Invalid UTF-8 in string".to_string(),
+                        Box::new(i.clone()),
+                    )),
+                };
+            }
+            Some(b) => *b,
+            None => break,
+        };
+        buf.push(b);
+    }
+    Result::Abort(Error::new(
+        "Unterminated string".to_string(),
+        Box::new(i.clone()),
+    ))
+}
+
 // TODO(jwall): We need a helper to convert Optional into failures.
 // TODO(jwall): We need a helper to convert std::result::Result into failures.
@@ -17,7 +17,8 @@ use super::{
     Result,
 };
 
-make_fn!(proto<StrIter, &str>,
+make_fn!(
+    proto<StrIter<'_>, &str>,
     do_each!(
         proto => until!(text_token!("://")),
         _ => must!(text_token!("://")),
@@ -25,19 +26,18 @@ make_fn!(proto<StrIter, &str>,
     )
 );
 
-make_fn!(domain<StrIter, &str>,
-    until!(either!(
-        discard!(text_token!("/")),
-        discard!(ascii_ws),
-        eoi))
+make_fn!(
+    domain<StrIter<'_>, &str>,
+    until!(either!(discard!(text_token!("/")), discard!(ascii_ws), eoi))
 );
 
-make_fn!(path<StrIter, &str>,
-     until!(either!(discard!(ascii_ws), eoi))
+make_fn!(
+    path<StrIter<'_>, &str>,
+    until!(either!(discard!(ascii_ws), eoi))
 );
 
 make_fn!(
-    sliceit<SliceIter<u8>, ()>,
+    sliceit<SliceIter<'_, u8>, ()>,
     do_each!(
         _ => input!(),
         end_of_input => eoi,
@@ -46,7 +46,7 @@ make_fn!(
 );
 
 make_fn!(
-    long_string_path<SliceIter<u8>, ()>,
+    long_string_path<SliceIter<'_, u8>, ()>,
     do_each!(
         _ => input!(),
         end_of_input => eoi,
@@ -54,7 +54,7 @@ make_fn!(
     )
 );
 
-make_fn!(pub url<StrIter, (Option<&str>, Option<&str>, &str)>,
+make_fn!(pub url<StrIter<'_>, (Option<&str>, Option<&str>, &str)>,
     do_each!(
         _ => input!(),
         protocol => optional!(proto),
@@ -64,6 +64,31 @@ make_fn!(pub url<StrIter, (Option<&str>, Option<&str>, &str)>,
     )
 );
 
+// Parses alternating runs like "abab." where each rule refers to the other.
+make_fn!(
+    alternating_a<StrIter<'_>, usize>,
+    either!(
+        do_each!(
+            _ => text_token!("a"),
+            n => call_ref!(self::alternating_b),
+            (n + 1)
+        ),
+        do_each!(
+            _ => text_token!("."),
+            (0)
+        )
+    )
+);
+
+make_fn!(
+    alternating_b<StrIter<'_>, usize>,
+    do_each!(
+        _ => text_token!("b"),
+        n => call_ref!(self::alternating_a),
+        (n + 1)
+    )
+);
+
 #[test]
 fn test_url_parser() {
     let iter = StrIter::new("http://example.com/some/path ");
@@ -89,3 +114,15 @@ fn test_slice_iter_make_fn_long_error_path() {
     let result = long_string_path(iter);
     assert!(result.is_fail());
 }
+
+#[test]
+fn test_call_ref_mutual_recursion() {
+    let iter = StrIter::new("abab.");
+    let result = alternating_a(iter);
+    assert!(result.is_complete());
+    if let Result::Complete(_, count) = result {
+        assert_eq!(count, 4);
+    }
+    let iter = StrIter::new("aba.");
+    assert!(alternating_a(iter).is_fail());
+}
@@ -13,10 +13,13 @@
 //  limitations under the License.
 
 //! Contains implementations of `InputIter`.
+use std::cell::{Cell, RefCell};
 use std::fmt::Debug;
+use std::io::{self, BufRead};
 use std::iter::Iterator;
+use std::rc::Rc;
 
-use super::{InputIter, Offsetable, Positioned, Seekable, Span, SpanRange};
+use super::{InputIter, Offsetable, Positioned, Seekable, Span, SpanRange, TextSpan};
 
 /// Implements `InputIter` for any slice of T.
 #[derive(Debug)]
@@ -28,10 +31,14 @@ pub struct SliceIter<'a, T: Debug + 'a> {
 impl<'a, T: Debug + 'a> SliceIter<'a, T> {
     /// new constructs a SliceIter from a Slice of T.
     pub fn new(source: &'a [T]) -> Self {
-        SliceIter {
-            source: source,
-            offset: 0,
-        }
+        SliceIter { source, offset: 0 }
+    }
+}
+
+impl<'a, T: Debug + Clone + 'a> SliceIter<'a, T> {
+    /// Returns an owned copy of the spanned range so it can outlive the input.
+    pub fn span_owned<R: Into<SpanRange>>(&self, idx: R) -> Vec<T> {
+        self.span(idx).to_vec()
     }
 }
 
@@ -76,17 +83,17 @@ where
 
 impl<'a, T: Debug + 'a> Clone for SliceIter<'a, T> {
     fn clone(&self) -> Self {
-        SliceIter {
-            source: self.source,
-            offset: self.offset,
-        }
+        *self
     }
 }
 
+// Only a slice reference and an offset so copies are as cheap as clones.
+impl<'a, T: Debug + 'a> Copy for SliceIter<'a, T> {}
+
 impl<'a, T: Debug + 'a> InputIter for SliceIter<'a, T> {
     fn curr(&self) -> Self::Item {
         if self.offset >= self.source.len() {
-            self.source.get(self.source.len() - 1).unwrap()
+            self.source.last().unwrap()
         } else {
             if self.offset == 0 {
                 self.source.get(self.offset).unwrap()
@@ -95,19 +102,39 @@ impl<'a, T: Debug + 'a> InputIter for SliceIter<'a, T> {
             }
         }
     }
+
+    fn remaining_len(&self) -> Option<usize> {
+        Some(self.source.len() - self.offset)
+    }
 }
 
 impl<'a, T: Debug + 'a> Span<&'a [T]> for SliceIter<'a, T> {
-    fn span(&self, idx: SpanRange) -> &'a [T] {
-        match idx {
+    fn span<R: Into<SpanRange>>(&self, idx: R) -> &'a [T] {
+        match idx.into() {
             SpanRange::Range(r) => self.source.index(r),
             SpanRange::RangeTo(r) => self.source.index(r),
             SpanRange::RangeFrom(r) => self.source.index(r),
             SpanRange::RangeFull(r) => self.source.index(r),
+            SpanRange::RangeInclusive(r) => self.source.index(r),
         }
     }
 }
 
+impl<'a> TextSpan<'a> for SliceIter<'a, u8> {
+    fn text_span<R: Into<SpanRange>>(&self, idx: R) -> Option<&'a str> {
+        std::str::from_utf8(self.span(idx)).ok()
+    }
+}
+
+impl<'a, T: Debug + 'a> Seekable for SliceIter<'a, T> {
+    fn seek(&mut self, to: usize) -> usize {
+        let self_len = self.source.len();
+        let offset = if self_len > to { to } else { self_len };
+        self.offset = offset;
+        self.offset
+    }
+}
+
 impl<'a> From<&'a str> for SliceIter<'a, u8> {
     fn from(source: &'a str) -> Self {
         SliceIter::new(source.as_bytes())
@@ -145,12 +172,34 @@ impl<'a> StrIter<'a> {
     /// new constructs a StrIter from a Slice of T.
     pub fn new(source: &'a str) -> Self {
         StrIter {
-            source: source,
+            source,
             offset: 0,
             line: 1,
             column: 1,
         }
     }
+
+    /// Returns the full source lines covering the range. The range is expanded
+    /// outward to the nearest newlines which are not included.
+    pub fn span_lines<R: Into<SpanRange>>(&self, idx: R) -> &'a str {
+        let bytes = self.source.as_bytes();
+        let (start, end) = match idx.into() {
+            SpanRange::Range(r) => (r.start, r.end),
+            SpanRange::RangeTo(r) => (0, r.end),
+            SpanRange::RangeFrom(r) => (r.start, bytes.len()),
+            SpanRange::RangeFull(_) => (0, bytes.len()),
+            SpanRange::RangeInclusive(r) => (*r.start(), *r.end() + 1),
+        };
+        let start = match bytes[..start].iter().rposition(|b| *b == b'\n') {
+            Some(nl) => nl + 1,
+            None => 0,
+        };
+        let end = match bytes[end..].iter().position(|b| *b == b'\n') {
+            Some(nl) => end + nl,
+            None => bytes.len(),
+        };
+        &self.source[start..end]
+    }
 }
 
 impl<'a> Iterator for StrIter<'a> {
@@ -192,19 +241,16 @@ impl<'a> Positioned for StrIter<'a> {
 
 impl<'a> Clone for StrIter<'a> {
     fn clone(&self) -> Self {
-        StrIter {
-            source: self.source,
-            offset: self.offset,
-            line: self.line,
-            column: self.column,
-        }
+        *self
     }
 }
 
+impl<'a> Copy for StrIter<'a> {}
+
 impl<'a> InputIter for StrIter<'a> {
     fn curr(&self) -> Self::Item {
         if self.offset >= self.source.len() {
-            self.source.as_bytes().get(self.source.len() - 1).unwrap()
+            self.source.as_bytes().last().unwrap()
         } else {
             if self.offset == 0 {
                 self.source.as_bytes().get(self.offset).unwrap()
@@ -213,6 +259,10 @@ impl<'a> InputIter for StrIter<'a> {
             }
         }
     }
+
+    fn remaining_len(&self) -> Option<usize> {
+        Some(self.source.len() - self.offset)
+    }
 }
 
 impl<'a> From<&'a str> for StrIter<'a> {
@@ -224,12 +274,13 @@ impl<'a> From<&'a str> for StrIter<'a> {
 use std::ops::Index;
 
 impl<'a> Span<&'a str> for StrIter<'a> {
-    fn span(&self, idx: SpanRange) -> &'a str {
-        match idx {
+    fn span<R: Into<SpanRange>>(&self, idx: R) -> &'a str {
+        match idx.into() {
             SpanRange::Range(r) => self.source.index(r),
             SpanRange::RangeTo(r) => self.source.index(r),
             SpanRange::RangeFrom(r) => self.source.index(r),
             SpanRange::RangeFull(r) => self.source.index(r),
+            SpanRange::RangeInclusive(r) => self.source.index(r),
         }
     }
 }
@@ -238,6 +289,13 @@ impl<'a> Seekable for StrIter<'a> {
     fn seek(&mut self, to: usize) -> usize {
         let self_len = self.source.len();
         let offset = if self_len > to { to } else { self_len };
+        // Recompute the line and column for the new offset.
+        let consumed = &self.source.as_bytes()[..offset];
+        self.line = 1 + consumed.iter().filter(|b| **b == b'\n').count();
+        self.column = match consumed.iter().rposition(|b| *b == b'\n') {
+            Some(nl) => offset - nl,
+            None => offset + 1,
+        };
         self.offset = offset;
         self.offset
     }
@@ -250,3 +308,404 @@ impl<'a> Peekable<&'a u8> for StrIter<'a> {
         self.source.as_bytes().get(self.offset)
     }
 }
+
+/// Implements `InputIter` over the `char`s of a str.
+///
+/// Offsets are byte offsets into the source so spans always land on utf-8
+/// character boundaries.
+#[derive(Debug)]
+pub struct CharIter<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> CharIter<'a> {
+    /// new constructs a CharIter from a str.
+    pub fn new(source: &'a str) -> Self {
+        CharIter {
+            source,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl<'a> Iterator for CharIter<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.source[self.offset..].chars().next() {
+            Some(c) => {
+                self.offset += c.len_utf8();
+                if c == '\n' {
+                    self.line += 1;
+                    self.column = 1;
+                } else {
+                    self.column += 1;
+                }
+                Some(c)
+            }
+            None => None,
+        }
+    }
+}
+
+impl<'a> Offsetable for CharIter<'a> {
+    fn get_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Positioned for CharIter<'a> {
+    fn line(&self) -> usize {
+        self.line
+    }
+
+    fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl<'a> Clone for CharIter<'a> {
+    fn clone(&self) -> Self {
+        CharIter {
+            source: self.source,
+            offset: self.offset,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+impl<'a> InputIter for CharIter<'a> {
+    fn curr(&self) -> Self::Item {
+        if self.offset == 0 {
+            self.source.chars().next().unwrap()
+        } else {
+            self.source[..self.offset].chars().next_back().unwrap()
+        }
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        Some(self.source[self.offset..].chars().count())
+    }
+}
+
+impl<'a> From<&'a str> for CharIter<'a> {
+    fn from(source: &'a str) -> Self {
+        Self::new(source)
+    }
+}
+
+impl<'a> Span<&'a str> for CharIter<'a> {
+    fn span<R: Into<SpanRange>>(&self, idx: R) -> &'a str {
+        match idx.into() {
+            SpanRange::Range(r) => self.source.index(r),
+            SpanRange::RangeTo(r) => self.source.index(r),
+            SpanRange::RangeFrom(r) => self.source.index(r),
+            SpanRange::RangeFull(r) => self.source.index(r),
+            SpanRange::RangeInclusive(r) => self.source.index(r),
+        }
+    }
+}
+
+impl<'a> Peekable<char> for CharIter<'a> {
+    fn peek_next(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+}
+
+/// Wraps an `InputIter` and records the furthest offset that it or any of its
+/// clones have read an item from.
+///
+/// Backtracking parsers usually report a failure at the start of the last
+/// branch they tried. The furthest offset reached is often a better location
+/// for the error. See the `track_furthest!` combinator.
+#[derive(Debug)]
+pub struct FurthestTracker<I> {
+    inner: I,
+    furthest: Rc<Cell<usize>>,
+}
+
+impl<I: Offsetable> FurthestTracker<I> {
+    /// new constructs a FurthestTracker wrapping an input.
+    pub fn new(inner: I) -> Self {
+        let offset = inner.get_offset();
+        FurthestTracker {
+            inner,
+            furthest: Rc::new(Cell::new(offset)),
+        }
+    }
+
+    /// Returns the furthest offset an item was read from since construction or
+    /// the last reset.
+    pub fn furthest_offset(&self) -> usize {
+        self.furthest.get()
+    }
+
+    /// Resets the furthest offset to the current offset.
+    pub fn reset_furthest(&self) {
+        self.furthest.set(self.inner.get_offset());
+    }
+
+    /// Returns the wrapped input.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I: Iterator + Offsetable> Iterator for FurthestTracker<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Record where the item was read from so a rejected item is reported
+        // at its own offset rather than the one after it.
+        let offset = self.inner.get_offset();
+        let item = self.inner.next();
+        if item.is_some() && offset > self.furthest.get() {
+            self.furthest.set(offset);
+        }
+        item
+    }
+}
+
+impl<I: Offsetable> Offsetable for FurthestTracker<I> {
+    fn get_offset(&self) -> usize {
+        self.inner.get_offset()
+    }
+}
+
+impl<I: Positioned> Positioned for FurthestTracker<I> {
+    fn line(&self) -> usize {
+        self.inner.line()
+    }
+
+    fn column(&self) -> usize {
+        self.inner.column()
+    }
+}
+
+impl<I: Clone> Clone for FurthestTracker<I> {
+    fn clone(&self) -> Self {
+        FurthestTracker {
+            inner: self.inner.clone(),
+            furthest: self.furthest.clone(),
+        }
+    }
+}
+
+impl<I: InputIter> InputIter for FurthestTracker<I> {
+    fn curr(&self) -> Self::Item {
+        self.inner.curr()
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        self.inner.remaining_len()
+    }
+
+    fn needs_more(&self) -> bool {
+        self.inner.needs_more()
+    }
+}
+
+impl<O, I: Span<O>> Span<O> for FurthestTracker<I> {
+    fn span<R: Into<SpanRange>>(&self, idx: R) -> O {
+        self.inner.span(idx)
+    }
+}
+
+impl<O, I: Peekable<O>> Peekable<O> for FurthestTracker<I> {
+    fn peek_next(&self) -> Option<O> {
+        self.inner.peek_next()
+    }
+}
+
+impl<I: Seekable> Seekable for FurthestTracker<I> {
+    fn seek(&mut self, to: usize) -> usize {
+        self.inner.seek(to)
+    }
+}
+
+#[derive(Debug)]
+struct ReadBuffer<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    // The offset of the first byte still held in buffer.
+    base: usize,
+    eof: bool,
+}
+
+impl<R> ReadBuffer<R> {
+    fn end(&self) -> usize {
+        self.base + self.buffer.len()
+    }
+
+    fn get(&self, offset: usize) -> Option<u8> {
+        offset
+            .checked_sub(self.base)
+            .and_then(|idx| self.buffer.get(idx))
+            .cloned()
+    }
+}
+
+/// Implements `InputIter` over the bytes of a `BufRead` without reading all of
+/// it into memory up front.
+///
+/// Only the data pulled in by `fill` is visible to parsers. Parsers that run
+/// out of buffered data before the reader is exhausted return
+/// `Result::Incomplete`. Call `fill` and then resume from the returned input to
+/// continue. Clones share the buffer.
+///
+/// Offsets are counted from the start of the reader. Use `discard_before` to
+/// drop data that has already been parsed.
+///
+/// Items are owned `u8`s since the buffer can grow underneath them.
+#[derive(Debug)]
+pub struct ReadIter<R> {
+    source: Rc<RefCell<ReadBuffer<R>>>,
+    offset: usize,
+}
+
+impl<R: BufRead> ReadIter<R> {
+    /// new constructs a ReadIter from a BufRead. No data is read until `fill` is called.
+    pub fn new(reader: R) -> Self {
+        ReadIter {
+            source: Rc::new(RefCell::new(ReadBuffer {
+                reader,
+                buffer: Vec::new(),
+                base: 0,
+                eof: false,
+            })),
+            offset: 0,
+        }
+    }
+
+    /// Reads the next chunk from the reader into the shared buffer and returns
+    /// how many bytes were read. Returns 0 once the reader is exhausted.
+    pub fn fill(&self) -> io::Result<usize> {
+        let mut source = self.source.borrow_mut();
+        let ReadBuffer {
+            reader,
+            buffer,
+            eof,
+            ..
+        } = &mut *source;
+        let chunk = reader.fill_buf()?;
+        let len = chunk.len();
+        buffer.extend_from_slice(chunk);
+        reader.consume(len);
+        if len == 0 {
+            *eof = true;
+        }
+        Ok(len)
+    }
+}
+
+impl<R> ReadIter<R> {
+    /// Returns true once `fill` has reached the end of the reader.
+    pub fn is_eof(&self) -> bool {
+        self.source.borrow().eof
+    }
+
+    /// Returns the number of bytes currently held in the buffer.
+    pub fn buffered_len(&self) -> usize {
+        self.source.borrow().buffer.len()
+    }
+
+    /// Drops the buffered data before `offset` from the shared buffer.
+    ///
+    /// Clones positioned before `offset` and spans reaching back before it
+    /// can no longer see the dropped data so only call this with the offset of
+    /// the oldest input still in use.
+    pub fn discard_before(&self, offset: usize) {
+        let mut source = self.source.borrow_mut();
+        let count = offset.saturating_sub(source.base).min(source.buffer.len());
+        source.buffer.drain(..count);
+        source.base += count;
+    }
+}
+
+impl<R> Iterator for ReadIter<R> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.source.borrow().get(self.offset);
+        if item.is_some() {
+            self.offset += 1;
+        }
+        item
+    }
+}
+
+impl<R> Offsetable for ReadIter<R> {
+    fn get_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<R> Clone for ReadIter<R> {
+    fn clone(&self) -> Self {
+        ReadIter {
+            source: self.source.clone(),
+            offset: self.offset,
+        }
+    }
+}
+
+impl<R> InputIter for ReadIter<R> {
+    /// Returns 0 if the byte is not buffered, e.g. before the first `fill`.
+    fn curr(&self) -> Self::Item {
+        let source = self.source.borrow();
+        let offset = if self.offset >= source.end() {
+            source.end().saturating_sub(1)
+        } else {
+            self.offset.saturating_sub(1)
+        };
+        source.get(offset).unwrap_or_default()
+    }
+
+    fn remaining_len(&self) -> Option<usize> {
+        let source = self.source.borrow();
+        if source.eof {
+            Some(source.end() - self.offset)
+        } else {
+            None
+        }
+    }
+
+    fn needs_more(&self) -> bool {
+        let source = self.source.borrow();
+        !source.eof && self.offset >= source.end()
+    }
+}
+
+impl<R> Span<Vec<u8>> for ReadIter<R> {
+    fn span<Idx: Into<SpanRange>>(&self, idx: Idx) -> Vec<u8> {
+        let source = self.source.borrow();
+        let (start, end) = match idx.into() {
+            SpanRange::Range(r) => (r.start, r.end),
+            SpanRange::RangeTo(r) => (source.base, r.end),
+            SpanRange::RangeFrom(r) => (r.start, source.end()),
+            SpanRange::RangeFull(_) => (source.base, source.end()),
+            SpanRange::RangeInclusive(r) => (*r.start(), *r.end() + 1),
+        };
+        source.buffer[start - source.base..end - source.base].to_vec()
+    }
+}
+
+impl<R> Seekable for ReadIter<R> {
+    fn seek(&mut self, to: usize) -> usize {
+        let source = self.source.borrow();
+        self.offset = to.max(source.base).min(source.end());
+        self.offset
+    }
+}
+
+impl<R> Peekable<u8> for ReadIter<R> {
+    fn peek_next(&self) -> Option<u8> {
+        self.source.borrow().get(self.offset)
+    }
+}
@@ -103,6 +103,7 @@
 //! # }
 //! ```
 use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::iter::Iterator;
 use std::result;
 
@@ -113,7 +114,26 @@ pub trait Offsetable {
 
 impl Offsetable for usize {
     fn get_offset(&self) -> usize {
-        return *self;
+        *self
+    }
+}
+
+/// A cheap snapshot of a parse position.
+///
+/// Checkpoints only record an offset so they can be compared and used to
+/// span the input no matter which iterator produced them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checkpoint(usize);
+
+impl From<usize> for Checkpoint {
+    fn from(offset: usize) -> Self {
+        Checkpoint(offset)
+    }
+}
+
+impl Offsetable for Checkpoint {
+    fn get_offset(&self) -> usize {
+        self.0
     }
 }
 
@@ -127,17 +147,73 @@ pub trait Positioned {
     fn column(&self) -> usize;
 }
 
+/// Deprecated name for `Positioned`.
+///
+/// Every `Positioned` type implements it so existing bounds keep working.
+#[deprecated(note = "Use Positioned instead")]
+pub trait TextPositionTracker: Positioned {}
+
+#[allow(deprecated)]
+impl<T: Positioned> TextPositionTracker for T {}
+
 /// SpanRange encompasses the valid Ops::Range types for use with the Span trait.
 pub enum SpanRange {
     Range(std::ops::Range<usize>),
     RangeTo(std::ops::RangeTo<usize>),
     RangeFrom(std::ops::RangeFrom<usize>),
     RangeFull(std::ops::RangeFull),
+    RangeInclusive(std::ops::RangeInclusive<usize>),
+}
+
+impl From<std::ops::Range<usize>> for SpanRange {
+    fn from(r: std::ops::Range<usize>) -> Self {
+        SpanRange::Range(r)
+    }
+}
+
+impl From<std::ops::RangeTo<usize>> for SpanRange {
+    fn from(r: std::ops::RangeTo<usize>) -> Self {
+        SpanRange::RangeTo(r)
+    }
+}
+
+impl From<std::ops::RangeFrom<usize>> for SpanRange {
+    fn from(r: std::ops::RangeFrom<usize>) -> Self {
+        SpanRange::RangeFrom(r)
+    }
+}
+
+impl From<std::ops::RangeFull> for SpanRange {
+    fn from(r: std::ops::RangeFull) -> Self {
+        SpanRange::RangeFull(r)
+    }
+}
+
+impl From<std::ops::RangeInclusive<usize>> for SpanRange {
+    fn from(r: std::ops::RangeInclusive<usize>) -> Self {
+        SpanRange::RangeInclusive(r)
+    }
 }
 
 /// An input that can provide a span of a range of the input.
+///
+/// The range can be a `SpanRange` or any of the range types it wraps.
 pub trait Span<O> {
-    fn span(&self, idx: SpanRange) -> O;
+    fn span<R: Into<SpanRange>>(&self, idx: R) -> O;
+}
+
+/// An input that can provide a span of a range of the input as text.
+///
+/// This is implemented for every input that spans as `&str` and for byte
+/// slices. It returns None if a byte span is not valid utf-8.
+pub trait TextSpan<'a> {
+    fn text_span<R: Into<SpanRange>>(&self, idx: R) -> Option<&'a str>;
+}
+
+impl<'a, I: Span<&'a str>> TextSpan<'a> for I {
+    fn text_span<R: Into<SpanRange>>(&self, idx: R) -> Option<&'a str> {
+        Some(self.span(idx))
+    }
 }
 
 pub trait Peekable<O> {
@@ -147,6 +223,23 @@ pub trait Peekable<O> {
 /// A Cloneable Iterator that can report an offset as a count of processed Items.
 pub trait InputIter: Iterator + Clone + Offsetable {
     fn curr(&self) -> Self::Item;
+
+    /// Returns the number of items left in the input if it is known.
+    ///
+    /// Inputs that can't know their length, like streaming inputs, return None.
+    fn remaining_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns true if the input has run out of items but more may still
+    /// arrive.
+    ///
+    /// Parsers return `Result::Incomplete` instead of failing when they reach
+    /// the end of such an input. Inputs that hold all of their data return
+    /// false.
+    fn needs_more(&self) -> bool {
+        false
+    }
 }
 
 /// The custom error type for use in `Result::{Fail, Abort}`.
@@ -170,7 +263,7 @@ impl<C> Error<C> {
     }
 
     /// Constructs a new Error with an offset and a cause.
-    pub fn caused_by<'a, D: Into<String>>(msg: D, cause: Box<Self>, ctx: Box<C>) -> Self {
+    pub fn caused_by<D: Into<String>>(msg: D, cause: Box<Self>, ctx: Box<C>) -> Self {
         Error {
             msg: msg.into(),
             cause: Some(cause),
@@ -179,21 +272,50 @@ impl<C> Error<C> {
     }
 
     /// Returns the msg.
-    pub fn get_msg<'a>(&'a self) -> &str {
+    pub fn get_msg(&self) -> &str {
         &self.msg
     }
 
     /// Returns `Some(cause)` if there is one, None otherwise.
-    pub fn get_cause<'a>(&'a self) -> Option<&'a Error<C>> {
-        match self.cause {
-            Some(ref e) => Some(e),
-            None => None,
-        }
+    pub fn get_cause(&self) -> Option<&Error<C>> {
+        self.cause.as_deref()
     }
 
     pub fn get_context(&self) -> &C {
         self.context.as_ref()
     }
+
+    /// Consumes the Error and returns the context.
+    pub fn into_context(self) -> C {
+        *self.context
+    }
+}
+
+impl<C: Offsetable> Error<C> {
+    /// Collapses consecutive errors in the cause chain that have the same msg
+    /// and offset into a single error.
+    pub fn dedup_causes(&mut self) {
+        let mut curr = self;
+        loop {
+            let duplicate = match curr.cause {
+                Some(ref c) => c.msg == curr.msg && c.get_offset() == curr.get_offset(),
+                None => return,
+            };
+            if duplicate {
+                let cause = curr.cause.take().unwrap();
+                curr.cause = cause.cause;
+            } else {
+                curr = curr.cause.as_mut().unwrap();
+            }
+        }
+    }
+}
+
+impl<C: Positioned> Error<C> {
+    /// Returns the line and column at which this Error happened.
+    pub fn get_position(&self) -> (usize, usize) {
+        (self.context.line(), self.context.column())
+    }
 }
 
 impl<C: Offsetable> Offsetable for Error<C> {
@@ -203,6 +325,26 @@ impl<C: Offsetable> Offsetable for Error<C> {
     }
 }
 
+// Errors are compared by msg, offset, and cause so that errors from
+// different copies of the same input position are equal.
+impl<C: Offsetable> PartialEq for Error<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.msg == other.msg
+            && self.get_offset() == other.get_offset()
+            && self.cause == other.cause
+    }
+}
+
+impl<C: Offsetable> Eq for Error<C> {}
+
+impl<C: Offsetable> Hash for Error<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.msg.hash(state);
+        self.get_offset().hash(state);
+        self.cause.hash(state);
+    }
+}
+
 impl<C: Offsetable> Display for Error<C> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> result::Result<(), std::fmt::Error> {
         write!(f, "{}", self.msg)?;
@@ -216,7 +358,7 @@ impl<C: Offsetable> Display for Error<C> {
 impl<C: Offsetable + Debug> std::error::Error for Error<C> {}
 
 /// The result of a parsing attempt.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Result<I: InputIter, O> {
     /// Complete represents a successful match.
     Complete(I, O),
@@ -232,46 +374,77 @@ pub enum Result<I: InputIter, O> {
 impl<I: InputIter, O> Result<I, O> {
     /// Returns true if the Result is Complete.
     pub fn is_complete(&self) -> bool {
-        if let &Result::Complete(_, _) = self {
-            return true;
-        }
-        return false;
+        matches!(self, Result::Complete(_, _))
     }
 
     /// Returns true if the Result is Incomoplete.
     pub fn is_incomplete(&self) -> bool {
-        if let &Result::Incomplete(_) = self {
-            return true;
-        }
-        return false;
+        matches!(self, Result::Incomplete(_))
     }
 
     /// Returns true if the Result is Fail.
     pub fn is_fail(&self) -> bool {
-        if let &Result::Fail(_) = self {
-            return true;
-        }
-        return false;
+        matches!(self, Result::Fail(_))
     }
 
     /// Returns true if the Result is Abort.
     pub fn is_abort(&self) -> bool {
-        if let &Result::Abort(_) = self {
-            return true;
+        matches!(self, Result::Abort(_))
+    }
+
+    /// Returns the input from an Incomplete Result so that parsing can be
+    /// resumed once more input is available. Returns None for every other variant.
+    pub fn incomplete_iter(self) -> Option<I> {
+        match self {
+            Result::Incomplete(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the remaining input for Complete and Incomplete
+    /// Results. Returns None for Fail and Abort.
+    pub fn remaining(&self) -> Option<&I> {
+        match self {
+            Result::Complete(i, _) => Some(i),
+            Result::Incomplete(i) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl<I: InputIter, O: Display> Display for Result<I, O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> result::Result<(), std::fmt::Error> {
+        match self {
+            Result::Complete(i, o) => write!(f, "Complete@{}: {}", i.get_offset(), o),
+            Result::Incomplete(i) => write!(f, "Incomplete@{}", i.get_offset()),
+            Result::Fail(e) => write!(f, "Fail: {}", e),
+            Result::Abort(e) => write!(f, "Abort: {}", e),
         }
-        return false;
     }
 }
 
 pub use combinators::*;
+pub use iter::CharIter;
+pub use iter::ReadIter;
 pub use iter::SliceIter;
 pub use iter::StrIter;
 
+pub mod classes;
 #[macro_use]
 pub mod combinators;
 pub mod iter;
+pub mod packrat;
+pub mod parser;
+pub mod recovery;
+pub mod render;
+pub mod stream;
+pub mod trace;
 
+// The macros import `run!` for downstream crates but inside this crate it is
+// already in textual scope so the import looks unused to the tests.
 #[cfg(test)]
+#[allow(unused_imports)]
 mod integration_tests;
 #[cfg(test)]
+#[allow(unused_imports)]
 mod test;
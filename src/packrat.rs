@@ -0,0 +1,83 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains the cache used by the `memoize!` combinator for packrat parsing.
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::{InputIter, Result};
+
+/// Caches the results of a single rule keyed by the offset the rule started at.
+///
+/// Use one PackratContext per memoized rule and pass it to `memoize!`. The
+/// rule is then run at most once per offset no matter how often the parser
+/// backtracks over it.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::ascii_digit;
+/// # use abortable_parser::packrat::PackratContext;
+/// # fn main() {
+/// let cache = PackratContext::new();
+/// let iter = StrIter::new("1");
+/// assert!(memoize!(iter, cache, ascii_digit).is_complete());
+/// assert!(memoize!(iter, cache, ascii_digit).is_complete());
+/// assert_eq!(cache.len(), 1);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PackratContext<I: InputIter, O> {
+    cache: RefCell<HashMap<usize, Result<I, O>>>,
+}
+
+impl<I: InputIter, O: Clone> PackratContext<I, O> {
+    /// Constructs an empty PackratContext.
+    pub fn new() -> Self {
+        PackratContext {
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a copy of the cached result for the offset if there is one.
+    pub fn get(&self, offset: usize) -> Option<Result<I, O>> {
+        self.cache.borrow().get(&offset).cloned()
+    }
+
+    /// Caches the result for the offset.
+    pub fn insert(&self, offset: usize, result: Result<I, O>) {
+        self.cache.borrow_mut().insert(offset, result);
+    }
+
+    /// Returns the number of cached offsets.
+    pub fn len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Returns true if nothing has been cached.
+    pub fn is_empty(&self) -> bool {
+        self.cache.borrow().is_empty()
+    }
+
+    /// Removes every cached result.
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+impl<I: InputIter, O: Clone> Default for PackratContext<I, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
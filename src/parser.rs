@@ -0,0 +1,199 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains the `Parser` trait for parsers that are values rather than macros.
+use std::marker::PhantomData;
+
+use super::{InputIter, Result};
+
+/// A parser that can be stored and passed around as a value.
+///
+/// Every `Fn(I) -> Result<I, O>` is a Parser so any parser function works as
+/// is. The provided methods compose parsers much like the macros do.
+///
+/// ```
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::{ascii_alpha, ascii_digit};
+/// # use abortable_parser::parser::Parser;
+/// let alnum = ascii_alpha.or(ascii_digit).map(|b| b as char);
+/// let result = alnum.parse(StrIter::new("1"));
+/// assert!(result.is_complete());
+/// ```
+pub trait Parser<I: InputIter, O> {
+    /// Runs the parser against the input.
+    fn parse(&self, input: I) -> Result<I, O>;
+
+    /// Applies a function to the output of a successful parse.
+    fn map<F, O2>(self, f: F) -> Map<Self, F, O>
+    where
+        Self: Sized,
+        F: Fn(O) -> O2,
+    {
+        Map {
+            parser: self,
+            f,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Tries another parser from the same input if this one fails.
+    ///
+    /// Incomplete and Abort results are returned without trying the other
+    /// parser just like `either!`.
+    fn or<P>(self, other: P) -> Or<Self, P>
+    where
+        Self: Sized,
+        P: Parser<I, O>,
+    {
+        Or {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Uses the output of a successful parse to choose the parser that runs
+    /// next on the remaining input.
+    fn and_then<F, P, O2>(self, f: F) -> AndThen<Self, F, O>
+    where
+        Self: Sized,
+        F: Fn(O) -> P,
+        P: Parser<I, O2>,
+    {
+        AndThen {
+            parser: self,
+            f,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Boxes the parser so that parsers of different types can be stored and
+    /// returned as the same type.
+    fn boxed<'a>(self) -> BoxedParser<'a, I, O>
+    where
+        Self: Sized + 'a,
+    {
+        BoxedParser::new(self)
+    }
+}
+
+impl<I, O, F> Parser<I, O> for F
+where
+    I: InputIter,
+    F: Fn(I) -> Result<I, O>,
+{
+    fn parse(&self, input: I) -> Result<I, O> {
+        self(input)
+    }
+}
+
+/// The Parser returned by `Parser::map`.
+pub struct Map<P, F, O> {
+    parser: P,
+    f: F,
+    phantom: PhantomData<fn() -> O>,
+}
+
+impl<I, O, O2, P, F> Parser<I, O2> for Map<P, F, O>
+where
+    I: InputIter,
+    P: Parser<I, O>,
+    F: Fn(O) -> O2,
+{
+    fn parse(&self, input: I) -> Result<I, O2> {
+        match self.parser.parse(input) {
+            Result::Complete(i, o) => Result::Complete(i, (self.f)(o)),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }
+}
+
+/// The Parser returned by `Parser::or`.
+pub struct Or<P1, P2> {
+    first: P1,
+    second: P2,
+}
+
+impl<I, O, P1, P2> Parser<I, O> for Or<P1, P2>
+where
+    I: InputIter,
+    P1: Parser<I, O>,
+    P2: Parser<I, O>,
+{
+    fn parse(&self, input: I) -> Result<I, O> {
+        match self.first.parse(input.clone()) {
+            Result::Fail(_) => self.second.parse(input),
+            result => result,
+        }
+    }
+}
+
+/// The Parser returned by `Parser::and_then`.
+pub struct AndThen<P, F, O> {
+    parser: P,
+    f: F,
+    phantom: PhantomData<fn() -> O>,
+}
+
+impl<I, O, O2, P, P2, F> Parser<I, O2> for AndThen<P, F, O>
+where
+    I: InputIter,
+    P: Parser<I, O>,
+    F: Fn(O) -> P2,
+    P2: Parser<I, O2>,
+{
+    fn parse(&self, input: I) -> Result<I, O2> {
+        match self.parser.parse(input) {
+            Result::Complete(i, o) => (self.f)(o).parse(i),
+            Result::Incomplete(ctx) => Result::Incomplete(ctx),
+            Result::Fail(e) => Result::Fail(e),
+            Result::Abort(e) => Result::Abort(e),
+        }
+    }
+}
+
+/// A type erased Parser.
+///
+/// Useful for recursive grammars and parsers chosen at runtime whose concrete
+/// types would otherwise be unnameable.
+///
+/// ```
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::{ascii_alpha, ascii_digit};
+/// # use abortable_parser::parser::{BoxedParser, Parser};
+/// let parsers: Vec<BoxedParser<StrIter, char>> = vec![
+///     ascii_alpha.map(|b| b as char).boxed(),
+///     ascii_digit.map(|b| (b - b'0' + b'a') as char).boxed(),
+/// ];
+/// assert!(parsers[1].parse(StrIter::new("1")).is_complete());
+/// ```
+pub struct BoxedParser<'a, I, O> {
+    parser: Box<dyn Parser<I, O> + 'a>,
+}
+
+impl<'a, I: InputIter, O> BoxedParser<'a, I, O> {
+    /// Constructs a BoxedParser from any Parser.
+    pub fn new<P: Parser<I, O> + 'a>(parser: P) -> Self {
+        BoxedParser {
+            parser: Box::new(parser),
+        }
+    }
+}
+
+impl<'a, I: InputIter, O> Parser<I, O> for BoxedParser<'a, I, O> {
+    fn parse(&self, input: I) -> Result<I, O> {
+        self.parser.parse(input)
+    }
+}
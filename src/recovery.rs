@@ -0,0 +1,161 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains helpers for parsing an entire input while recovering from errors.
+use std::cell::{Ref, RefCell};
+
+use super::{Error, InputIter, Result};
+
+/// The range of offsets in the input that a parsed item came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// The offset where the item started.
+    pub start: usize,
+    /// The offset just past the end of the item.
+    pub end: usize,
+}
+
+/// Collects the errors a parse recovered from so that a single parse can
+/// report many problems.
+///
+/// Pass it as the last argument to `recover!` to record each recovered error.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::ascii_digit;
+/// # use abortable_parser::recovery::ParseSession;
+/// # fn main() {
+/// let session = ParseSession::new();
+/// let iter = StrIter::new("x;");
+/// let result = recover!(iter, ascii_digit, text_token!(";"), b'0', session);
+/// assert!(result.is_complete());
+/// assert_eq!(session.errors().len(), 1);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ParseSession<I> {
+    errors: RefCell<Vec<Error<I>>>,
+}
+
+impl<I> ParseSession<I> {
+    /// Constructs a ParseSession with no errors.
+    pub fn new() -> Self {
+        ParseSession {
+            errors: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Records an error that the parse recovered from.
+    pub fn record(&self, err: Error<I>) {
+        self.errors.borrow_mut().push(err);
+    }
+
+    /// Returns the errors recorded so far.
+    pub fn errors(&self) -> Ref<'_, Vec<Error<I>>> {
+        self.errors.borrow()
+    }
+
+    /// Consumes the session returning the recorded errors.
+    pub fn into_errors(self) -> Vec<Error<I>> {
+        self.errors.into_inner()
+    }
+}
+
+impl<I> Default for ParseSession<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Skips input until the sync rule matches and consumes the match. Returns None
+// if the input ended first.
+fn synchronize<I, S, SO>(mut input: I, sync: &S) -> Option<I>
+where
+    I: InputIter,
+    S: Fn(I) -> Result<I, SO>,
+{
+    loop {
+        if let Result::Complete(i, _) = sync(input.clone()) {
+            return Some(i);
+        }
+        input.next()?;
+    }
+}
+
+/// Parses items until the end of the input collecting every successful item and
+/// every error.
+///
+/// When an item fails or aborts the error is recorded and the input is skipped
+/// past the next match of the sync rule before parsing resumes. Each successful
+/// item is returned with the `SourceSpan` it was parsed from.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # use abortable_parser::recovery::parse_all_recovering;
+/// # fn main() {
+/// let (items, errors) = parse_all_recovering(
+///     StrIter::new("a;1;"),
+///     |i| do_each!(i, a => ascii_alpha, _ => text_token!(";"), (a)),
+///     |i| text_token!(i, ";"),
+/// );
+/// assert_eq!(items.len(), 1);
+/// assert_eq!(errors.len(), 1);
+/// # }
+/// ```
+pub fn parse_all_recovering<I, O, P, S, SO>(
+    input: I,
+    item: P,
+    sync: S,
+) -> (Vec<(O, SourceSpan)>, Vec<Error<I>>)
+where
+    I: InputIter,
+    P: Fn(I) -> Result<I, O>,
+    S: Fn(I) -> Result<I, SO>,
+{
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut input = input;
+    loop {
+        if input.clone().next().is_none() {
+            break;
+        }
+        let start = input.get_offset();
+        let err = match item(input.clone()) {
+            Result::Complete(i, o) => {
+                if i.get_offset() > start {
+                    let end = i.get_offset();
+                    items.push((o, SourceSpan { start, end }));
+                    input = i;
+                    continue;
+                }
+                Error::new("Item did not consume any input", Box::new(input.clone()))
+            }
+            Result::Incomplete(ctx) => {
+                errors.push(Error::new("Unexpected End Of Input", Box::new(ctx)));
+                break;
+            }
+            Result::Fail(e) => e,
+            Result::Abort(e) => e,
+        };
+        errors.push(err);
+        input = match synchronize(input, &sync) {
+            Some(i) => i,
+            None => break,
+        };
+    }
+    (items, errors)
+}
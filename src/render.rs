@@ -0,0 +1,74 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains helpers for rendering errors against their source text.
+use super::iter::StrIter;
+use super::{Error, Offsetable, Positioned};
+
+const TAB_WIDTH: usize = 4;
+
+/// Renders an error compiler style with the message, the source line it
+/// occurred on, and a caret under the column it occurred at.
+///
+/// Tabs in the source line are expanded to the next multiple of 4 columns so
+/// the caret stays aligned.
+///
+/// ```
+/// use abortable_parser::iter::StrIter;
+/// use abortable_parser::render::render_error;
+/// use abortable_parser::Error;
+/// let mut iter = StrIter::new("let x = ;");
+/// for _ in 0..8 {
+///     iter.next();
+/// }
+/// let err = Error::new("Expected an expression", Box::new(iter));
+/// assert_eq!(
+///     render_error(&err),
+///     "Expected an expression at line 1, column 9\nlet x = ;\n        ^"
+/// );
+/// ```
+pub fn render_error(err: &Error<StrIter>) -> String {
+    let ctx = err.get_context();
+    let offset = ctx.get_offset();
+    let line = ctx.span_lines(offset..offset);
+    let prefix_len = (ctx.column() - 1).min(line.len());
+    let prefix_chars = String::from_utf8_lossy(&line.as_bytes()[..prefix_len])
+        .chars()
+        .count();
+    let mut rendered_line = String::new();
+    // The number of chars in rendered_line so far.
+    let mut col = 0;
+    let mut caret_col = 0;
+    for (i, c) in line.chars().enumerate() {
+        if c == '\t' {
+            let width = TAB_WIDTH - col % TAB_WIDTH;
+            rendered_line.push_str(&" ".repeat(width));
+            col += width;
+        } else {
+            rendered_line.push(c);
+            col += 1;
+        }
+        if i + 1 == prefix_chars {
+            caret_col = col;
+        }
+    }
+    format!(
+        "{} at line {}, column {}\n{}\n{}^",
+        err.get_msg(),
+        ctx.line(),
+        ctx.column(),
+        rendered_line,
+        " ".repeat(caret_col)
+    )
+}
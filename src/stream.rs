@@ -0,0 +1,181 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains iterators that lazily apply parsers to an input.
+use std::iter::Iterator;
+use std::result;
+
+use super::{Error, InputIter, Result};
+
+/// An iterator that lazily parses a list of items separated by a separator.
+///
+/// Constructed with `separated_iter`.
+pub struct SeparatedIter<I, S, P> {
+    input: Option<I>,
+    sep: S,
+    item: P,
+    first: bool,
+}
+
+/// Constructs an iterator that yields items parsed by `item` separated by
+/// matches of `sep` one at a time.
+///
+/// Iteration stops at the first input that isn't a separator or an item or at
+/// the end of the input. An item that fails after a separator or an Abort is
+/// yielded as an `Err` and ends the iteration.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::combinators::ascii_alpha;
+/// # use abortable_parser::stream::separated_iter;
+/// # fn main() {
+/// let items: Vec<u8> = separated_iter(
+///     StrIter::new("a,b"),
+///     |i| text_token!(i, ","),
+///     ascii_alpha,
+/// )
+/// .map(|r| r.unwrap())
+/// .collect();
+/// assert_eq!(items, vec![b'a', b'b']);
+/// # }
+/// ```
+pub fn separated_iter<I, S, SO, P, O>(input: I, sep: S, item: P) -> SeparatedIter<I, S, P>
+where
+    I: InputIter,
+    S: Fn(I) -> Result<I, SO>,
+    P: Fn(I) -> Result<I, O>,
+{
+    SeparatedIter {
+        input: Some(input),
+        sep,
+        item,
+        first: true,
+    }
+}
+
+impl<I, S, P> SeparatedIter<I, S, P> {
+    /// Returns the input where iteration will resume or None if iteration
+    /// has stopped.
+    pub fn input(&self) -> Option<&I> {
+        self.input.as_ref()
+    }
+}
+
+impl<I, S, SO, P, O> Iterator for SeparatedIter<I, S, P>
+where
+    I: InputIter,
+    S: Fn(I) -> Result<I, SO>,
+    P: Fn(I) -> Result<I, O>,
+{
+    type Item = result::Result<O, Error<I>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.input.take()?;
+        let first = self.first;
+        self.first = false;
+        let item_input = if first {
+            input
+        } else {
+            match (self.sep)(input) {
+                Result::Complete(i, _) => i,
+                Result::Abort(e) => return Some(Err(e)),
+                // Anything else means the list is finished.
+                Result::Fail(_) | Result::Incomplete(_) => return None,
+            }
+        };
+        match (self.item)(item_input) {
+            Result::Complete(i, o) => {
+                self.input = Some(i);
+                Some(Ok(o))
+            }
+            Result::Abort(e) => Some(Err(e)),
+            Result::Fail(e) => {
+                if first {
+                    None
+                } else {
+                    Some(Err(e))
+                }
+            }
+            Result::Incomplete(_) => None,
+        }
+    }
+}
+
+/// An iterator that lazily applies a parser to an input yielding one parsed
+/// record per call to `next`.
+///
+/// Iteration stops at the end of the input. A Fail or an Abort is yielded as
+/// an `Err` and ends the iteration. So is an Incomplete, e.g. for a trailing
+/// partial record, with the input where the parser ran out as the context.
+///
+/// ```
+/// # #[macro_use] extern crate abortable_parser;
+/// # use abortable_parser::iter::StrIter;
+/// # use abortable_parser::Result;
+/// # use abortable_parser::stream::ParseIter;
+/// # fn main() {
+/// fn record(i: StrIter<'_>) -> Result<StrIter<'_>, &str> {
+///     text_token!(i, "a")
+/// }
+/// let records: Vec<&str> = ParseIter::new(StrIter::new("aa"), record)
+///     .map(|r| r.unwrap())
+///     .collect();
+/// assert_eq!(records, vec!["a", "a"]);
+/// # }
+/// ```
+pub struct ParseIter<I: InputIter, O> {
+    input: Option<I>,
+    parser: fn(I) -> Result<I, O>,
+}
+
+impl<I: InputIter, O> ParseIter<I, O> {
+    /// Constructs a ParseIter that parses records from input with parser.
+    pub fn new(input: I, parser: fn(I) -> Result<I, O>) -> Self {
+        ParseIter {
+            input: Some(input),
+            parser,
+        }
+    }
+
+    /// Returns the input where iteration will resume or None if iteration
+    /// has stopped.
+    pub fn input(&self) -> Option<&I> {
+        self.input.as_ref()
+    }
+}
+
+impl<I: InputIter, O> Iterator for ParseIter<I, O> {
+    type Item = result::Result<O, Error<I>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.input.take()?;
+        input.clone().next()?;
+        let offset = input.get_offset();
+        match (self.parser)(input) {
+            Result::Complete(i, o) => {
+                // A parser that consumes nothing would yield forever.
+                if i.get_offset() > offset {
+                    self.input = Some(i);
+                }
+                Some(Ok(o))
+            }
+            Result::Fail(e) | Result::Abort(e) => Some(Err(e)),
+            Result::Incomplete(ctx) => Some(Err(Error::new(
+                format!("Incomplete record at offset {}", ctx.get_offset()),
+                Box::new(ctx),
+            ))),
+        }
+    }
+}
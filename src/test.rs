@@ -14,13 +14,21 @@
 
 use std::fmt::{Debug, Display};
 
+use super::classes::ByteClass;
 use super::combinators::*;
+use super::packrat::PackratContext;
+use super::parser::{BoxedParser, Parser};
+use super::recovery::{parse_all_recovering, ParseSession, SourceSpan};
+use super::render::render_error;
+use super::stream::{separated_iter, ParseIter};
 use super::{
-    iter::{SliceIter, StrIter},
-    InputIter, Offsetable, Positioned, Result,
+    iter::{CharIter, FurthestTracker, ReadIter, SliceIter, StrIter},
+    Checkpoint, Error, InputIter, Offsetable, Peekable, Positioned, Result, Seekable, Span,
+    SpanRange, TextSpan,
 };
 
 #[test]
+#[allow(clippy::clone_on_copy)]
 fn test_slice_iter() {
     let input_str = "foo";
     let mut iter = SliceIter::new(input_str.as_bytes());
@@ -33,23 +41,74 @@ fn test_slice_iter() {
             Some(b) => b,
         };
         assert_eq!(*b as char, *iter.curr() as char);
-        out.push(b.clone());
+        out.push(*b);
     }
-    assert_eq!(*iter.curr(), 'o' as u8);
+    assert_eq!(*iter.curr(), b'o');
     assert_eq!(3, out.len());
-    assert_eq!('f' as u8, out[0]);
-    assert_eq!('o' as u8, out[1]);
-    assert_eq!('o' as u8, out[2]);
+    assert_eq!(b'f', out[0]);
+    assert_eq!(b'o', out[1]);
+    assert_eq!(b'o', out[2]);
     assert_eq!(3, iter.get_offset());
 
     out = Vec::new();
     for b in cloned {
-        out.push(b.clone());
+        out.push(*b);
     }
     assert_eq!(3, out.len());
-    assert_eq!('f' as u8, out[0]);
-    assert_eq!('o' as u8, out[1]);
-    assert_eq!('o' as u8, out[2]);
+    assert_eq!(b'f', out[0]);
+    assert_eq!(b'o', out[1]);
+    assert_eq!(b'o', out[2]);
+}
+
+#[test]
+fn test_span_range_forms() {
+    let iter = StrIter::new("foobar");
+    assert_eq!(iter.span(1..3), "oo");
+    assert_eq!(iter.span(..3), "foo");
+    assert_eq!(iter.span(3..), "bar");
+    assert_eq!(iter.span(..), "foobar");
+    assert_eq!(iter.span(SpanRange::Range(1..3)), "oo");
+}
+
+#[test]
+fn test_slice_iter_seek() {
+    let input_str = "foo";
+    let mut iter = SliceIter::new(input_str.as_bytes());
+    assert_eq!(iter.seek(1), 1);
+    assert_eq!(*iter.next().unwrap(), b'o');
+    assert_eq!(iter.seek(10), 3);
+    assert_eq!(iter.get_offset(), 3);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_char_iter() {
+    let input_str = "héllo";
+    let mut iter = CharIter::new(input_str);
+    assert_eq!(0, iter.get_offset());
+    let mut out = Vec::new();
+    loop {
+        let c = match iter.next() {
+            None => break,
+            Some(c) => c,
+        };
+        assert_eq!(c, iter.curr());
+        out.push(c);
+    }
+    assert_eq!(vec!['h', 'é', 'l', 'l', 'o'], out);
+    assert_eq!(input_str.len(), iter.get_offset());
+    assert_eq!(6, iter.get_offset());
+    assert_eq!(iter.column(), 6);
+}
+
+#[test]
+fn test_char_iter_span() {
+    let input_str = "héllo";
+    let mut iter = CharIter::new(input_str);
+    iter.next();
+    iter.next();
+    assert_eq!(iter.peek_next(), Some('l'));
+    assert_eq!("hé", iter.span(SpanRange::RangeTo(..iter.get_offset())));
 }
 
 fn will_fail<I, C>(i: I) -> Result<I, String>
@@ -104,6 +163,46 @@ where
 }
 
 #[test]
+fn test_error_dedup_causes() {
+    let iter = StrIter::new("foo");
+    let root = super::Error::new("root", Box::new(iter));
+    let dup = super::Error::caused_by("wrapped", Box::new(root), Box::new(iter));
+    let wrapped = super::Error::caused_by("wrapped", Box::new(dup), Box::new(iter));
+    let mut err = super::Error::caused_by("top", Box::new(wrapped), Box::new(iter));
+    err.dedup_causes();
+    let cause = err.get_cause().unwrap();
+    assert_eq!(cause.get_msg(), "wrapped");
+    let root_cause = cause.get_cause().unwrap();
+    assert_eq!(root_cause.get_msg(), "root");
+    assert!(root_cause.get_cause().is_none());
+}
+
+#[test]
+fn test_incomplete_iter() {
+    let input_str = "foo;";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = until!(iter, text_token!("; "));
+    assert!(result.is_incomplete());
+    let i = result.incomplete_iter();
+    assert!(i.is_some());
+    assert_eq!(i.unwrap().get_offset(), 4);
+    let iter = SliceIter::new(input_str.as_bytes());
+    assert!(text_token!(iter, "foo").incomplete_iter().is_none());
+}
+
+#[test]
+fn test_remaining() {
+    let iter = SliceIter::new("foobar".as_bytes());
+    let result = text_token!(iter.clone(), "foo");
+    assert_eq!(result.remaining().map(|i| i.get_offset()), Some(3));
+    let result = until!(iter.clone(), text_token!(";"));
+    assert_eq!(result.remaining().map(|i| i.get_offset()), Some(6));
+    let result = text_token!(iter, "bar");
+    assert!(result.remaining().is_none());
+}
+
+#[test]
+#[allow(clippy::clone_on_copy)]
 fn test_peek() {
     let input_str = "foo bar";
     let iter = SliceIter::new(input_str.as_bytes());
@@ -117,6 +216,31 @@ fn test_peek() {
 }
 
 #[test]
+fn test_peek_is_true() {
+    let input_str = "foo bar";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = peek_is!(iter, text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert!(o);
+        assert_eq!(i.get_offset(), 0);
+    }
+}
+
+#[test]
+fn test_peek_is_false() {
+    let input_str = "foo bar";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = peek_is!(iter, will_fail);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert!(!o);
+        assert_eq!(i.get_offset(), 0);
+    }
+}
+
+#[test]
+#[allow(clippy::clone_on_copy)]
 fn test_not_success() {
     let input_str = "foo bar";
     let iter = SliceIter::new(input_str.as_bytes());
@@ -157,6 +281,36 @@ fn test_text_token_fails() {
     assert!(result.is_fail());
 }
 
+#[test]
+fn test_keyword() {
+    let input_str = "in x";
+    let iter = StrIter::new(input_str);
+    let result = keyword!(iter, "in");
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "in");
+        assert_eq!(i.get_offset(), 2);
+    }
+}
+
+#[test]
+fn test_keyword_at_eoi() {
+    let input_str = "in";
+    let iter = StrIter::new(input_str);
+    assert!(keyword!(iter, "in").is_complete());
+}
+
+#[test]
+fn test_keyword_fails_on_identifier() {
+    let input_str = "integer";
+    let iter = StrIter::new(input_str);
+    let result = keyword!(iter, "in");
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_offset(), 0);
+    }
+}
+
 #[test]
 fn test_wrap_err_fail() {
     let input_str = "foo";
@@ -181,488 +335,3002 @@ fn test_wrap_err_abort() {
     }
 }
 
+fn rewrite_error<I: InputIter>(e: super::Error<I>) -> super::Error<I> {
+    super::Error::new("rewritten", Box::new(e.get_context().clone()))
+}
+
 #[test]
-fn test_must_fails() {
+fn test_map_abort() {
     let input_str = "foo";
     let iter = SliceIter::new(input_str.as_bytes());
-    let result = must!(iter, will_fail);
+    let result = map_abort!(iter, must!(will_fail), rewrite_error);
     assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "rewritten");
+    }
 }
 
 #[test]
-fn test_must_succeed() {
+fn test_map_abort_leaves_fail() {
     let input_str = "foo";
     let iter = SliceIter::new(input_str.as_bytes());
-    let result = must!(iter, parse_byte);
-    assert!(result.is_complete());
+    let result = map_abort!(iter, will_fail, rewrite_error);
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_msg(), "AAAAHHH!!!");
+    }
 }
 
 #[test]
-fn test_trap_abort() {
+fn test_trace_parser_is_transparent() {
     let input_str = "foo";
     let iter = SliceIter::new(input_str.as_bytes());
-    let result = trap!(iter, must!(will_fail));
-    assert!(result.is_fail(), format!("{:?}", result));
+    let result = trace_parser!(iter, "foo", text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+    assert!(trace_parser!(iter, "fail", will_fail).is_fail());
+    assert!(trace_parser!(iter, "abort", must!(will_fail)).is_abort());
 }
 
-#[test]
-fn test_trap_incomplete() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = trap!(iter, will_not_complete);
-    assert!(result.is_incomplete(), format!("{:?}", result));
+#[cfg(feature = "trace")]
+static TRACE_LINES: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+#[cfg(feature = "trace")]
+fn capture_trace(line: &str) {
+    TRACE_LINES.lock().unwrap().push(line.to_string());
 }
 
+#[cfg(feature = "trace")]
 #[test]
-fn test_trap_fail() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = trap!(iter, will_fail);
-    assert!(result.is_fail(), format!("{:?}", result));
+fn test_trace_sink_records_lines() {
+    crate::trace::set_trace_sink(capture_trace);
+    let iter = StrIter::new("foo");
+    let result = trace_parser!(iter, "sink test", text_token!("foo"));
+    crate::trace::clear_trace_sink();
+    assert!(result.is_complete());
+    let lines = TRACE_LINES.lock().unwrap();
+    assert!(lines.iter().any(|l| l == "sink test @0: Complete"));
+}
+
+fn take_n<'a>(mut i: SliceIter<'a, u8>, n: usize) -> Result<SliceIter<'a, u8>, usize> {
+    for _ in 0..n {
+        if i.next().is_none() {
+            return Result::Incomplete(i);
+        }
+    }
+    Result::Complete(i, n)
 }
 
 #[test]
-fn test_trap_complete() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = trap!(iter, parse_byte);
-    assert!(result.is_complete(), format!("{:?}", result));
+fn test_complete_function_with_args() {
+    let iter = SliceIter::new("fo".as_bytes());
+    assert!(take_n(iter, 3).is_incomplete());
+    let result = complete!(iter, "Not enough bytes", take_n(3));
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_msg(), "Not enough bytes");
+    }
+    let result = complete!(iter, "Not enough bytes", take_n(2));
+    assert!(result.is_complete());
+}
+
+fn byte_of<'a>(mut i: SliceIter<'a, u8>, b: u8) -> Result<SliceIter<'a, u8>, u8> {
+    let start = i;
+    match i.next() {
+        Some(item) if *item == b => Result::Complete(i, b),
+        Some(_) => Result::Fail(Error::new(
+            format!("Expected {}", b as char),
+            Box::new(start),
+        )),
+        None => Result::Incomplete(start),
+    }
 }
 
 #[test]
-fn test_must_complete() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let iter_fail = iter.clone();
-    let mut result = must_complete!(iter, "AHHH".to_string(), will_not_complete);
+fn test_error_macros_keep_input_context() {
+    let mut iter = SliceIter::new("abc".as_bytes());
+    iter.next();
+    let result = wrap_err!(iter, byte_of(b'x'), "outer");
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_msg(), "outer");
+        assert_eq!(e.get_context().get_offset(), 1);
+        let cause = e.get_cause().unwrap();
+        assert_eq!(cause.get_msg(), "Expected x");
+        assert_eq!(cause.get_context().get_offset(), 1);
+    }
+    let result = with_err!(iter, byte_of(b'x'), "replaced");
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_msg(), "replaced");
+        assert!(e.get_cause().is_none());
+        assert_eq!(e.get_context().get_offset(), 1);
+    }
+    let result = must_complete!(iter, "Ran out".to_string(), run!(take_n(3)));
     assert!(result.is_abort());
-    result = must_complete!(iter_fail, "AHHH".to_string(), will_fail);
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_context().get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_incomplete_carries_input() {
+    let mut iter = SliceIter::new("ab".as_bytes());
+    iter.next();
+    iter.next();
+    let result = parse_byte(iter);
+    assert!(result.is_incomplete());
+    if let Result::Incomplete(ctx) = result {
+        assert_eq!(ctx.get_offset(), 2);
+    }
+    let result = will_not_complete(iter);
+    assert_eq!(result.incomplete_iter().map(|i| i.get_offset()), Some(2));
+    let result = complete!(iter, "Out of input", parse_byte);
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_context().get_offset(), 2);
+    }
+    let result = must_complete!(iter, "Out of input".to_string(), parse_byte);
     assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_context().get_offset(), 2);
+    }
 }
 
 #[test]
-fn test_do_each() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = do_each!(iter,
-        b1 => parse_byte,
-        _ => parse_byte,
-        b3 => parse_byte,
-        (b1, b3)
-    );
+fn test_must_string() {
+    let iter = SliceIter::new("foo".as_bytes());
+    let bytes = "foo".as_bytes();
+    let result = must_string(Result::Complete(iter, bytes.iter().collect()), "Bad utf8");
     assert!(result.is_complete());
     if let Result::Complete(_, o) = result {
-        assert_eq!('f' as u8, o.0);
-        assert_eq!('o' as u8, o.1);
-    } else {
-        assert!(false, "did not get a tuple of 2 items");
+        assert_eq!(o, "foo");
+    }
+    let invalid = [0xffu8, 0xfe];
+    let result = must_string(Result::Complete(iter, invalid.iter().collect()), "Bad utf8");
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Bad utf8");
     }
 }
 
 #[test]
-fn test_do_each_input_and_token() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = do_each!(iter,
-        _ => input!(),
-        token => text_token!("foo"),
-        (token)
-    );
+fn test_bytes_to_string() {
+    let iter = SliceIter::new("abc123".as_bytes());
+    let result = bytes_to_string!(iter.clone(), repeat!(ascii_alphanumeric));
     assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert_eq!("foo", o);
-    } else {
-        assert!(false, "did not get our token");
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "abc123");
+        assert_eq!(i.get_offset(), 6);
+    }
+    let result = bytes_to_string!(iter, repeat!(ascii_digit));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "");
+        assert_eq!(i.get_offset(), 0);
     }
+    let invalid = [0xffu8, 0xfe];
+    let iter = SliceIter::new(&invalid);
+    let result = bytes_to_string!(iter, repeat!(parse_byte));
+    assert!(result.is_abort());
 }
 
 #[test]
-fn test_either_idents() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = either!(iter, will_fail, will_fail, parse_three);
+fn test_repeat_into_string() {
+    let iter = CharIter::new("2024-01");
+    let result = repeat_into!(iter, String, satisfy!(|c: char| c.is_ascii_digit()));
     assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert_eq!("foo".to_string(), o);
-    } else {
-        assert!(false, "Didn't not successfully match");
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "2024");
+        assert_eq!(i.get_offset(), 4);
     }
 }
 
 #[test]
-fn test_either_macros() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = either!(iter, run!(will_fail), run!(will_fail), run!(parse_three));
+fn test_repeat_into_btreeset() {
+    let iter = SliceIter::new("3113a".as_bytes());
+    let result = repeat_into!(iter, std::collections::BTreeSet<u8>, ascii_digit);
     assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert_eq!("foo".to_string(), o);
-    } else {
-        assert!(false, "Didn't successfully match");
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o.into_iter().collect::<Vec<u8>>(), vec![b'1', b'3']);
+        assert_eq!(i.get_offset(), 4);
     }
+    let iter = SliceIter::new("1".as_bytes());
+    let result = repeat_into!(iter, std::collections::BTreeSet<u8>, must!(ascii_alpha));
+    assert!(result.is_abort());
 }
 
 #[test]
-fn test_either_fail() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = either!(iter, run!(will_fail), run!(will_fail));
+fn test_abort_if_number_too_large() {
+    let iter = StrIter::new("255");
+    let result = abort_if!(iter, signed_integer, |n: &i64| *n > 255, "Too large");
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, 255);
+    }
+    let iter = StrIter::new("256");
+    let result = abort_if!(iter, signed_integer, |n: &i64| *n > 255, "Too large");
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Too large");
+        assert_eq!(e.get_context().get_offset(), 0);
+    }
+    let iter = StrIter::new("x");
+    let result = abort_if!(iter, signed_integer, |n: &i64| *n > 255, "Too large");
     assert!(result.is_fail());
 }
 
 #[test]
-fn test_either_abort() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = either!(iter, must!(will_fail), parse_three, run!(will_fail));
-    assert!(result.is_abort());
+fn test_not_followed_by_allowed() {
+    let iter = SliceIter::new("foobaz".as_bytes());
+    let result = not_followed_by!(iter, text_token!("foo"), text_token!("bar"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+    let iter = SliceIter::new("foo".as_bytes());
+    let result = not_followed_by!(iter, text_token!("foo"), ascii_alpha);
+    assert!(result.is_complete());
 }
 
 #[test]
-fn test_optional_some() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = optional!(iter, parse_byte);
-    assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert_eq!('f' as u8, o.unwrap());
-    } else {
-        assert!(false, "optional! did not complete");
+fn test_not_followed_by_forbidden() {
+    let iter = SliceIter::new("foobar".as_bytes());
+    let result = not_followed_by!(iter, text_token!("foo"), text_token!("bar"));
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_context().get_offset(), 0);
     }
+    let iter = SliceIter::new("foobar".as_bytes());
+    let result = not_followed_by!(iter, text_token!("foo"), must!(ascii_digit));
+    assert!(result.is_abort());
 }
 
 #[test]
-fn test_optional_none() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = optional!(iter, will_fail);
+fn test_followed_by_success() {
+    let iter = StrIter::new("12px");
+    let result = followed_by!(iter, signed_integer, text_token!("px"));
     assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert!(o.is_none(), "output was not none");
-    } else {
-        assert!(false, "optional! did not complete");
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, 12);
+        assert_eq!(i.get_offset(), 2);
     }
 }
 
 #[test]
-fn test_optional_abort() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = optional!(iter, must!(will_fail));
-    assert!(result.is_abort(), "optional did not abort");
+fn test_followed_by_fail() {
+    let iter = StrIter::new("12em");
+    let result = followed_by!(iter, signed_integer, text_token!("px"));
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_context().get_offset(), 0);
+        assert!(e.get_cause().is_some());
+    }
 }
 
 #[test]
-fn test_repeat() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = repeat!(iter, parse_byte);
+fn test_permutation_any_order() {
+    let iter = SliceIter::new("b a c".as_bytes());
+    let result = permutation!(
+        iter,
+        do_each!(_ => repeat!(ascii_ws), a => text_token!("a"), (a)),
+        do_each!(_ => repeat!(ascii_ws), b => text_token!("b"), (b)),
+        do_each!(_ => repeat!(ascii_ws), c => text_token!("c"), (c)),
+    );
     assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert_eq!(3, o.len());
-        assert_eq!('f' as u8, o[0]);
-        assert_eq!('o' as u8, o[1]);
-        assert_eq!('o' as u8, o[2]);
-    } else {
-        assert!(false, "repeat did not parse succesfully");
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, ("a", "b", "c"));
+        assert_eq!(i.get_offset(), 5);
     }
 }
 
 #[test]
-fn test_repeat_fail() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = repeat!(iter, will_fail);
+fn test_permutation_missing_parser() {
+    let iter = SliceIter::new("ba".as_bytes());
+    let result = permutation!(iter, text_token!("a"), text_token!("b"), text_token!("c"));
+    assert!(result.is_fail());
+    let iter = SliceIter::new("1a".as_bytes());
+    let result = permutation!(iter, ascii_alpha, ascii_digit);
     assert!(result.is_complete());
     if let Result::Complete(_, o) = result {
-        assert_eq!(0, o.len());
-    } else {
-        assert!(false, "repeat did not parse succesfully");
+        assert_eq!(o, (b'a', b'1'));
+    }
+    let iter = SliceIter::new("a".as_bytes());
+    let result = permutation!(iter, ascii_alpha, must!(ascii_digit));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_alt_complete_skips_incomplete() {
+    let iter = StrIter::new("foo");
+    let result = either!(iter.clone(), until!(text_token!(";")), text_token!("foo"));
+    assert!(result.is_incomplete());
+    let result = alt_complete!(iter.clone(), until!(text_token!(";")), text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+    let iter = SliceIter::new("foo".as_bytes());
+    let result = alt_complete!(iter, will_not_complete, run!(will_fail),);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_longest_prefers_longer_match() {
+    let iter = SliceIter::new("foobar".as_bytes());
+    let result = longest!(iter.clone(), text_token!("foo"), text_token!("foobar"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foobar");
+        assert_eq!(i.get_offset(), 6);
+    }
+    let result = longest!(
+        iter.clone(),
+        text_token!("foo"),
+        do_each!(f => text_token!("fo"), _ => text_token!("o"), (f)),
+    );
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, "foo");
+    } else {
+        panic!("longest! did not complete");
+    }
+    let result = longest!(iter.clone(), text_token!("bar"), text_token!("baz"));
+    assert!(result.is_fail());
+    let result = longest!(iter, text_token!("foobar"), must!(text_token!("x")));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_parse_all_consumed() {
+    let iter = SliceIter::new("foo".as_bytes());
+    let result = parse_all!(iter, text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_parse_all_leftover() {
+    let iter = SliceIter::new("foobar".as_bytes());
+    let result = parse_all!(iter, text_token!("foo"));
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_msg(), "Unconsumed input at offset 3");
+        assert_eq!(e.get_context().get_offset(), 3);
+    }
+}
+
+make_fn!(generic_foo<'a, I, &'a str> where I: InputIter<Item = &'a u8> + TextSpan<'a>,
+    text_token!("foo")
+);
+
+make_fn!(generic_digits<'a, I, Vec<u8>> where I: InputIter<Item = &'a u8>,
+    repeat!(ascii_digit)
+);
+
+#[test]
+fn test_make_fn_generic_input() {
+    let result = generic_foo(SliceIter::new("foo".as_bytes()));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+    let result = generic_foo(StrIter::new("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+    let result = generic_digits(StrIter::new("12a"));
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, vec![b'1', b'2']);
+    } else {
+        panic!("generic_digits did not complete");
+    }
+}
+
+make_fn!(
+    identifier_span<'a, StrIter<'a>, &'a str>,
+    consume_all!(ascii_alphanumeric)
+);
+
+#[test]
+fn test_make_fn_lifetime_output() {
+    let input = String::from("abc123 rest");
+    let span: &str = {
+        let result = identifier_span(StrIter::new(&input));
+        assert!(result.is_complete());
+        if let Result::Complete(i, o) = result {
+            assert_eq!(i.get_offset(), 6);
+            o
+        } else {
+            unreachable!()
+        }
+    };
+    assert_eq!(span, "abc123");
+}
+
+#[test]
+fn test_tuple_three() {
+    let iter = SliceIter::new("a1b".as_bytes());
+    let result = tuple!(iter, ascii_alpha, ascii_digit, text_token!("b"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, (b'a', b'1', "b"));
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_tuple_middle_failure() {
+    let iter = SliceIter::new("aab".as_bytes());
+    let result = tuple!(iter, ascii_alpha, text_token!("1"), text_token!("b"));
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_context().get_offset(), 1);
+    }
+    let result = tuple!(iter, ascii_alpha, must!(text_token!("1")), must!(will_fail));
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_context().get_offset(), 1);
+    }
+}
+
+#[test]
+fn test_opt_vec() {
+    let iter = SliceIter::new("[1,2]".as_bytes());
+    let result = opt_vec!(
+        iter,
+        do_each!(
+            _ => text_token!("["),
+            items => separated!(text_token!(","), ascii_digit),
+            _ => text_token!("]"),
+            (items)
+        )
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec![b'1', b'2']);
+        assert_eq!(i.get_offset(), 5);
+    }
+    let iter = SliceIter::new("end".as_bytes());
+    let result = opt_vec!(
+        iter,
+        do_each!(
+            _ => text_token!("["),
+            items => separated!(text_token!(","), ascii_digit),
+            _ => text_token!("]"),
+            (items)
+        )
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert!(o.is_empty());
+        assert_eq!(i.get_offset(), 0);
+    }
+}
+
+#[test]
+fn test_either_seek_matches_either() {
+    for input in ["foo", "bar", "baz"].iter() {
+        let iter = StrIter::new(input);
+        let seeked = either_seek!(iter.clone(), text_token!("foo"), text_token!("bar"));
+        let cloned = either!(iter, text_token!("foo"), text_token!("bar"));
+        assert_eq!(seeked.is_complete(), cloned.is_complete());
+        assert_eq!(seeked.is_fail(), cloned.is_fail());
+        if let (Result::Complete(si, so), Result::Complete(ci, co)) = (seeked, cloned) {
+            assert_eq!(so, co);
+            assert_eq!(si.get_offset(), 3);
+            assert_eq!(si.get_offset(), ci.get_offset());
+        }
+    }
+}
+
+#[test]
+fn test_either_seek_rewinds_consumed_input() {
+    let iter = StrIter::new("foo");
+    let result = either_seek!(
+        iter,
+        do_each!(
+            _ => text_token!("fo"),
+            _ => text_token!("x"),
+            ("fox")
+        ),
+        text_token!("foo")
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_either_seek_restores_offset() {
+    let mut iter = StrIter::new("xfoo");
+    iter.next();
+    let result = either_seek!(
+        iter,
+        text_token!("fob"),
+        text_token!("fo"),
+        text_token!("f")
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "fo");
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_rewind_match() {
+    let mut iter = SliceIter::new("xfoo".as_bytes());
+    iter.next();
+    let result = rewind!(iter, text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, Some("foo"));
+        assert_eq!(i.get_offset(), 1);
+    }
+}
+
+#[test]
+fn test_rewind_no_match() {
+    let mut iter = SliceIter::new("xfoo".as_bytes());
+    iter.next();
+    let result = rewind!(iter, ascii_digit);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, None);
+        assert_eq!(i.get_offset(), 1);
+    }
+    let result = do_each!(iter,
+        probe => rewind!(text_token!("fo")),
+        word => text_token!("foo"),
+        (probe, word)
+    );
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, (Some("fo"), "foo"));
+        assert_eq!(i.get_offset(), 4);
+    } else {
+        panic!("do_each! with rewind! did not complete");
+    }
+}
+
+#[test]
+fn test_slice_iter_span_owned() {
+    let owned: Vec<u8> = {
+        let input = String::from("foobar");
+        let iter = SliceIter::new(input.as_bytes());
+        iter.span_owned(1..4)
+    };
+    assert_eq!(owned, b"oob".to_vec());
+    let iter = SliceIter::new("foo".as_bytes());
+    assert_eq!(iter.span_owned(..), b"foo".to_vec());
+}
+
+#[test]
+fn test_peek_next_macro() {
+    let iter = StrIter::new("foo");
+    let result = peek_next!(iter);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, Some(&b'f'));
+        assert_eq!(i.get_offset(), 0);
+    }
+    let iter = CharIter::new("");
+    let result: Result<CharIter, Option<char>> = peek_next!(iter);
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, None);
+    }
+}
+
+fn dispatch_token(i: SliceIter<'_, u8>) -> Result<SliceIter<'_, u8>, &'_ str> {
+    dispatch!(i, {
+        b'(' => text_token!("()"),
+        b'"' => text_token!("\"\""),
+        _ => text_token!("nil"),
+    })
+}
+
+#[test]
+fn test_dispatch() {
+    let result = dispatch_token(SliceIter::new("()".as_bytes()));
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "()");
+        assert_eq!(i.get_offset(), 2);
+    } else {
+        panic!("Failed to dispatch (");
+    }
+    let result = dispatch_token(SliceIter::new("\"\"".as_bytes()));
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "\"\"");
+        assert_eq!(i.get_offset(), 2);
+    } else {
+        panic!("Failed to dispatch a quote");
+    }
+    let result = dispatch_token(SliceIter::new("nil".as_bytes()));
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "nil");
+        assert_eq!(i.get_offset(), 3);
+    } else {
+        panic!("Failed to dispatch the fallthrough");
+    }
+    assert!(dispatch_token(SliceIter::new("(x".as_bytes())).is_fail());
+    assert!(dispatch_token(SliceIter::new("".as_bytes())).is_fail());
+}
+
+#[test]
+fn test_byte_class_membership() {
+    let class = ByteClass::from_ranges(&[b'a'..=b'f', b'0'..=b'9']).with_bytes(b"_\xff");
+    for b in 0..=255u8 {
+        let expected = (b'a'..=b'f').contains(&b) || b.is_ascii_digit() || b == b'_' || b == 0xff;
+        assert_eq!(class.matches(b), expected, "byte {}", b);
+    }
+    assert!(!ByteClass::new().matches(0));
+    assert_eq!(
+        ByteClass::from_bytes(b"ab"),
+        ByteClass::from_ranges(&[b'a'..=b'b'])
+    );
+}
+
+#[test]
+fn test_satisfy_class() {
+    let hex = ByteClass::from_ranges(&[b'a'..=b'f', b'0'..=b'9']);
+    let iter = SliceIter::new("1fz".as_bytes());
+    let result = repeat!(iter.clone(), satisfy_class!(hex));
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec![b'1', b'f']);
+        assert_eq!(i.get_offset(), 2);
+    } else {
+        panic!("repeat! of satisfy_class! did not complete");
+    }
+    let result = satisfy_class!(SliceIter::new("z".as_bytes()), hex);
+    assert!(result.is_fail());
+    let result = satisfy_class!(SliceIter::new("".as_bytes()), hex);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_read_iter_across_small_buffer() {
+    use std::io::{BufReader, Cursor};
+    let reader = BufReader::with_capacity(4, Cursor::new(b"12345;rest".to_vec()));
+    let iter = ReadIter::new(reader);
+    assert_eq!(iter.fill().unwrap(), 4);
+    let result = until!(iter.clone(), satisfy!(|b: u8| b == b';'));
+    assert!(result.is_incomplete());
+    if let Result::Incomplete(ctx) = result {
+        assert_eq!(ctx.get_offset(), 4);
+    }
+    assert_eq!(iter.fill().unwrap(), 4);
+    let result = until!(iter.clone(), satisfy!(|b: u8| b == b';'));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, b"12345".to_vec());
+        assert_eq!(i.get_offset(), 5);
+        assert_eq!(i.peek_next(), Some(b';'));
+    }
+    assert_eq!(iter.remaining_len(), None);
+    assert_eq!(iter.fill().unwrap(), 2);
+    assert_eq!(iter.fill().unwrap(), 0);
+    assert!(iter.is_eof());
+    assert_eq!(iter.buffered_len(), 10);
+    assert_eq!(iter.remaining_len(), Some(10));
+    assert_eq!(iter.collect::<Vec<u8>>(), b"12345;rest".to_vec());
+}
+
+#[test]
+fn test_read_iter_incomplete_at_buffer_boundary() {
+    use std::io::{BufReader, Cursor};
+    let reader = BufReader::with_capacity(2, Cursor::new(b"ab".to_vec()));
+    let iter = ReadIter::new(reader);
+    assert_eq!(iter.curr(), 0);
+    assert!(satisfy!(iter.clone(), |b: u8| b == b'a').is_incomplete());
+    assert!(eoi(iter.clone()).is_incomplete());
+    assert_eq!(iter.fill().unwrap(), 2);
+    let result = parse_all!(iter.clone(), satisfy!(|b: u8| b == b'a'));
+    assert!(result.is_fail());
+    let result = parse_all!(iter.clone(), repeat!(satisfy!(|b: u8| b != b';')));
+    assert!(result.is_incomplete());
+    assert_eq!(iter.fill().unwrap(), 0);
+    let result = parse_all!(iter.clone(), repeat!(satisfy!(|b: u8| b != b';')));
+    assert!(result.is_complete());
+    assert!(eoi(iter.clone()).is_fail());
+}
+
+#[test]
+fn test_peek_count_read_iter() {
+    use std::io::{BufReader, Cursor};
+    let reader = BufReader::with_capacity(2, Cursor::new(b"  x".to_vec()));
+    let iter = ReadIter::new(reader);
+    assert_eq!(iter.fill().unwrap(), 2);
+    let result = peek_count!(iter.clone(), |b: u8| b == b' ');
+    assert!(result.is_incomplete());
+    assert_eq!(iter.fill().unwrap(), 1);
+    let result = peek_count!(iter.clone(), |b: u8| b == b' ');
+    assert!(result.is_complete());
+    if let Result::Complete(i, count) = result {
+        assert_eq!(i.get_offset(), 0);
+        assert_eq!(count, 2);
+    }
+}
+
+#[test]
+fn test_read_iter_discard_before() {
+    use std::io::{BufReader, Cursor};
+    let reader = BufReader::with_capacity(4, Cursor::new(b"abcdefgh".to_vec()));
+    let iter = ReadIter::new(reader);
+    assert_eq!(iter.fill().unwrap(), 4);
+    let result = until!(iter.clone(), satisfy!(|b: u8| b == b'c'));
+    let mut iter = if let Result::Complete(i, _) = result {
+        i
+    } else {
+        panic!("until! did not complete");
+    };
+    iter.discard_before(iter.get_offset());
+    assert_eq!(iter.buffered_len(), 2);
+    assert_eq!(iter.get_offset(), 2);
+    assert_eq!(iter.fill().unwrap(), 4);
+    assert_eq!(iter.buffered_len(), 6);
+    assert_eq!(iter.span(SpanRange::Range(2..5)), b"cde".to_vec());
+    assert_eq!(iter.seek(0), 2);
+    assert_eq!(iter.next(), Some(b'c'));
+    assert_eq!(iter.curr(), b'c');
+}
+
+#[test]
+fn test_str_iter_seek_restores_position() {
+    let mut iter = StrIter::new("ab\ncd\nef");
+    let mut walked = iter;
+    for _ in 0..7 {
+        walked.next();
+    }
+    iter.seek(7);
+    assert_eq!(
+        (iter.line(), iter.column()),
+        (walked.line(), walked.column())
+    );
+    assert_eq!((iter.line(), iter.column()), (3, 2));
+    iter.seek(4);
+    assert_eq!((iter.line(), iter.column()), (2, 2));
+    iter.seek(1);
+    assert_eq!((iter.line(), iter.column()), (1, 2));
+    iter.seek(100);
+    assert_eq!(iter.get_offset(), 8);
+    assert_eq!((iter.line(), iter.column()), (3, 3));
+    iter.seek(0);
+    assert_eq!((iter.line(), iter.column()), (1, 1));
+}
+
+#[test]
+fn test_span_lines() {
+    let iter = StrIter::new("first\nsecond line\nthird");
+    assert_eq!(iter.span_lines(9..13), "second line");
+    assert_eq!(span_lines!(iter, 9, 13), "second line");
+    assert_eq!(iter.span_lines(3..9), "first\nsecond line");
+    assert_eq!(iter.span_lines(..2), "first");
+    assert_eq!(iter.span_lines(20..), "third");
+    assert_eq!(iter.span_lines(6..6), "second line");
+}
+
+#[test]
+fn test_render_error_caret() {
+    let iter = StrIter::new("first\nlet x = ;\nlast");
+    let result = do_each!(iter,
+        _ => text_token!("first\nlet x = "),
+        e => with_err!(ascii_digit, "Expected an expression"),
+        (e)
+    );
+    if let Result::Fail(e) = result {
+        let rendered = render_error(&e);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "Expected an expression at line 2, column 9");
+        assert_eq!(lines[1], "let x = ;");
+        assert_eq!(lines[2].find('^'), Some(8));
+        assert_eq!(&lines[1][8..9], ";");
+    } else {
+        panic!("Expected a failure to render");
+    }
+}
+
+#[test]
+fn test_render_error_expands_tabs() {
+    let mut iter = StrIter::new("\tx\t= ;");
+    for _ in 0..5 {
+        iter.next();
+    }
+    let err = Error::new("Expected an expression", Box::new(iter));
+    let rendered = render_error(&err);
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines[1], "    x   = ;");
+    assert_eq!(lines[2], "          ^");
+}
+
+#[test]
+fn test_iters_are_copy() {
+    fn assert_copy<T: Copy>() {}
+    assert_copy::<StrIter>();
+    assert_copy::<SliceIter<u8>>();
+    let iter = StrIter::new("foo");
+    let copied = iter;
+    assert_eq!(
+        text_token!(iter, "foo").remaining().map(|i| i.get_offset()),
+        Some(3)
+    );
+    assert_eq!(copied.get_offset(), 0);
+}
+
+#[test]
+fn test_text_token_bytes() {
+    let input = b"foo\x00bar".to_vec();
+    let iter = SliceIter::new(&input);
+    let result = text_token_bytes!(iter.clone(), b"foo\x00");
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, b"foo\x00");
+        assert_eq!(o.as_ptr(), input.as_ptr());
+        assert_eq!(i.get_offset(), 4);
+    }
+    let result = text_token_bytes!(iter.clone(), b"fob");
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_context().get_offset(), 0);
+    }
+    let result = text_token_bytes!(SliceIter::new(b"fo"), b"foo");
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_text_token_returns_input_span() {
+    let input = "foo bar";
+    let result = text_token!(StrIter::new(input), "foo");
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(o.as_ptr(), input.as_ptr());
+    }
+    let result = text_token!(SliceIter::new(input.as_bytes()), "foo");
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(o.as_ptr(), input.as_ptr());
+    }
+}
+
+#[test]
+fn test_checkpoints() {
+    let iter = SliceIter::new("foo bar baz".as_bytes());
+    let result: Result<SliceIter<u8>, &[u8]> = do_each!(iter,
+        _ => text_token!("foo "),
+        start => checkpoint!(),
+        _ => text_token!("bar"),
+        end => checkpoint!(),
+        span => span_from_checkpoints!(start, end),
+        (span)
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 7);
+        assert_eq!(o, b"bar");
+    }
+    assert!(Checkpoint::from(4) < Checkpoint::from(7));
+}
+
+make_fn!(
+    text_line<'a, StrIter<'a>, &'a str>,
+    do_each!(
+        line => until!(text_token!("\n")),
+        _ => text_token!("\n"),
+        (line)
+    )
+);
+
+#[test]
+fn test_many_till() {
+    let iter = StrIter::new("one\ntwo\n\nrest");
+    let result = many_till!(iter, text_line, either!(discard!(text_token!("\n")), eoi));
+    assert!(result.is_complete());
+    if let Result::Complete(i, (lines, _)) = result {
+        assert_eq!(lines, vec!["one", "two"]);
+        assert_eq!(i.get_offset(), 9);
+    }
+
+    let iter = StrIter::new("one\ntwo\n");
+    let result = many_till!(iter, text_line, either!(discard!(text_token!("\n")), eoi));
+    assert!(result.is_complete());
+    if let Result::Complete(i, (lines, _)) = result {
+        assert_eq!(lines, vec!["one", "two"]);
+        assert_eq!(i.get_offset(), 8);
+    }
+}
+
+#[test]
+fn test_many_till_immediate_terminator() {
+    let iter = StrIter::new("\nrest");
+    let result = many_till!(iter, text_line, text_token!("\n"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, (lines, term)) = result {
+        assert!(lines.is_empty());
+        assert_eq!(term, "\n");
+        assert_eq!(i.get_offset(), 1);
+    }
+}
+
+#[test]
+fn test_skip_many() {
+    let iter = StrIter::new(" \t\n foo");
+    let result = skip_many!(iter, ascii_ws);
+    assert!(result.is_complete());
+    if let Result::Complete(i, ()) = result {
+        assert_eq!(i.get_offset(), 4);
+        assert_eq!(*i.clone().next().unwrap(), b'f');
+    }
+
+    let iter = StrIter::new("foo");
+    let result = skip_many!(iter, ascii_ws);
+    assert!(result.is_complete());
+    if let Result::Complete(i, ()) = result {
+        assert_eq!(i.get_offset(), 0);
+    }
+}
+
+#[test]
+fn test_skip_many_abort() {
+    let iter = StrIter::new("  foo");
+    let result = skip_many!(iter, must!(ascii_alpha));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_multispace() {
+    let result: Result<StrIter, &str> = multispace0(StrIter::new("   x"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "   ");
+        assert_eq!(i.get_offset(), 3);
+    }
+    let result: Result<StrIter, &str> = multispace1(StrIter::new("   x"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "   ");
+        assert_eq!(i.get_offset(), 3);
+    }
+    let result: Result<StrIter, &str> = multispace0(StrIter::new("x"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "");
+        assert_eq!(i.get_offset(), 0);
+    }
+    let result: Result<StrIter, &str> = multispace1(StrIter::new("x"));
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_offset(), 0);
+    }
+    let result: Result<SliceIter<u8>, &[u8]> = multispace1(SliceIter::new(" \tx".as_bytes()));
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, b" \t");
+    }
+}
+
+#[test]
+fn test_take_line() {
+    let iter = StrIter::new("one\n\ntwo");
+    let result: Result<StrIter, &str> = take_line!(iter);
+    assert!(result.is_complete());
+    let iter = if let Result::Complete(i, o) = result {
+        assert_eq!(o, "one");
+        assert_eq!(i.get_offset(), 4);
+        i
+    } else {
+        unreachable!()
+    };
+    let result: Result<StrIter, &str> = take_line!(iter, require_newline);
+    assert!(result.is_complete());
+    let iter = if let Result::Complete(i, o) = result {
+        assert_eq!(o, "");
+        assert_eq!(i.get_offset(), 5);
+        i
+    } else {
+        unreachable!()
+    };
+    let result: Result<StrIter, &str> = take_line!(iter, require_newline);
+    assert!(result.is_incomplete());
+    let result: Result<StrIter, &str> = take_line!(iter);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "two");
+        assert_eq!(i.get_offset(), 8);
+        let result: Result<StrIter, &str> = take_line!(i);
+        assert!(result.is_incomplete());
+    }
+}
+
+#[test]
+fn test_repeat_take_line() {
+    let iter = SliceIter::new("a\nb\nc".as_bytes());
+    let result: Result<SliceIter<u8>, Vec<&[u8]>> = repeat!(iter, take_line!());
+    assert!(result.is_complete());
+    if let Result::Complete(_, lines) = result {
+        assert_eq!(lines, vec![&b"a"[..], &b"b"[..], &b"c"[..]]);
+    }
+}
+
+#[test]
+fn test_rest() {
+    let iter = StrIter::new("head:body");
+    let result = do_each!(iter,
+        _ => text_token!("head:"),
+        body => rest!(),
+        (body)
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "body");
+        assert_eq!(i.get_offset(), 9);
+        assert!(eoi(i).is_complete());
+        let result: Result<StrIter, &str> = rest!(i);
+        assert!(result.is_complete());
+        if let Result::Complete(i, o) = result {
+            assert_eq!(o, "");
+            assert_eq!(i.get_offset(), 9);
+        }
+    }
+}
+
+#[test]
+fn test_span_range_inclusive() {
+    let iter = SliceIter::new("foobar".as_bytes());
+    assert_eq!(iter.span(1..=3), b"oob");
+    assert_eq!(iter.span(SpanRange::RangeInclusive(0..=5)), b"foobar");
+    let iter = StrIter::new("foobar");
+    assert_eq!(iter.span(1..=3), "oob");
+    assert_eq!(iter.span(SpanRange::RangeInclusive(3..=3)), "b");
+    assert_eq!(iter.span_lines(0..=2), "foobar");
+}
+
+#[test]
+fn test_terminated_list() {
+    let iter = StrIter::new("a;b;c;");
+    let result = terminated_list!(iter, consume_all!(ascii_alpha), text_token!(";"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec!["a", "b", "c"]);
+        assert_eq!(i.get_offset(), 6);
+    }
+
+    let iter = StrIter::new("a;b");
+    let result = terminated_list!(iter, consume_all!(ascii_alpha), text_token!(";"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec!["a"]);
+        assert_eq!(i.get_offset(), 2);
+    }
+}
+
+#[test]
+fn test_first_of() {
+    for (input, expected) in [("let", 0), ("if", 1), ("else", 2)] {
+        let iter = StrIter::new(input);
+        let result = first_of!(
+            iter,
+            text_token!("let"),
+            text_token!("if"),
+            text_token!("else"),
+        );
+        assert!(result.is_complete());
+        if let Result::Complete(i, (idx, o)) = result {
+            assert_eq!(idx, expected);
+            assert_eq!(o, input);
+            assert_eq!(i.get_offset(), input.len());
+        }
+    }
+    let iter = StrIter::new("while");
+    let result = first_of!(iter, text_token!("let"), text_token!("if"));
+    assert!(result.is_fail());
+    let iter = SliceIter::new("1".as_bytes());
+    let result = first_of!(iter, ascii_alpha, ascii_digit);
+    if let Result::Complete(_, (idx, o)) = result {
+        assert_eq!(idx, 1);
+        assert_eq!(o, b'1');
+    } else {
+        panic!("first_of! did not complete");
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr<'a> {
+    Num(i64),
+    Ident(&'a str),
+}
+
+fn expr(i: StrIter<'_>) -> Result<StrIter<'_>, Expr<'_>> {
+    tagged_either!(i, {
+        Expr::Num => signed_integer,
+        Expr::Ident => consume_all!(ascii_alpha),
+    })
+}
+
+#[test]
+fn test_tagged_either() {
+    let result = expr(StrIter::new("123"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, Expr::Num(123));
+        assert_eq!(i.get_offset(), 3);
+    }
+    let result = expr(StrIter::new("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, Expr::Ident("foo"));
+        assert_eq!(i.get_offset(), 3);
+    }
+    let result = tagged_either!(StrIter::new(";"), {
+        Expr::Num => signed_integer,
+    });
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_recover() {
+    let iter = StrIter::new("foo;b@d;bar;");
+    let result = many_till!(
+        iter,
+        recover!(
+            do_each!(
+                word => consume_all!(ascii_alpha),
+                _ => must!(text_token!(";")),
+                (word)
+            ),
+            text_token!(";"),
+            "<error>"
+        ),
+        eoi
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, (o, _)) = result {
+        assert_eq!(o, vec!["foo", "<error>", "bar"]);
+        assert_eq!(i.get_offset(), 12);
+    }
+
+    let iter = StrIter::new("b@d");
+    let result = recover!(iter, must!(text_token!("bad")), text_token!(";"), "<error>");
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_recover_with_session() {
+    let session = ParseSession::new();
+    let iter = StrIter::new("foo;b@d;bar;1x;baz;");
+    let result = many_till!(
+        iter,
+        recover!(
+            do_each!(
+                word => consume_all!(ascii_alpha),
+                _ => must!(text_token!(";")),
+                (word)
+            ),
+            text_token!(";"),
+            "<error>",
+            session
+        ),
+        eoi
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(_, (o, _)) = result {
+        assert_eq!(o, vec!["foo", "<error>", "bar", "<error>", "baz"]);
+    }
+    let errors = session.into_errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].get_offset(), 5);
+    assert_eq!(errors[1].get_offset(), 12);
+}
+
+#[test]
+fn test_error_eq_and_hash() {
+    use std::collections::HashSet;
+    let iter = StrIter::new("foo");
+    let first = text_token!(iter, "bar");
+    let second = text_token!(iter.clone(), "bar");
+    let (first, second) = match (first, second) {
+        (Result::Fail(a), Result::Fail(b)) => (a, b),
+        _ => panic!("text_token! did not fail"),
+    };
+    assert_eq!(first, second);
+    let mut moved = iter;
+    moved.next();
+    let other = Error::new(first.get_msg(), Box::new(moved));
+    assert_ne!(first, other);
+    let caused = Error::caused_by("outer", Box::new(first.clone()), Box::new(iter));
+    assert_ne!(caused, Error::new("outer", Box::new(iter)));
+    let mut set = HashSet::new();
+    set.insert(first);
+    set.insert(second);
+    set.insert(other);
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_result_display() {
+    let iter = StrIter::new("foo");
+    let result = text_token!(iter, "foo");
+    assert_eq!(result.to_string(), "Complete@3: foo");
+    let result = text_token!(iter, "bar");
+    assert_eq!(result.to_string(), "Fail: Expected bar but didn't get it.");
+    let result = must!(iter, text_token!("bar"));
+    assert_eq!(result.to_string(), "Abort: Expected bar but didn't get it.");
+    let result: Result<StrIter, &str> = Result::Incomplete(iter);
+    assert_eq!(result.to_string(), "Incomplete@0");
+}
+
+#[test]
+fn test_wrap_err_with_is_lazy() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    let called = AtomicBool::new(false);
+    let msg_fn = |i: &StrIter| {
+        called.store(true, Ordering::SeqCst);
+        format!("Expected keyword at {}", i.get_offset())
+    };
+    let iter = StrIter::new("foo");
+    let result = wrap_err_with!(iter, text_token!("foo"), msg_fn);
+    assert!(result.is_complete());
+    assert!(!called.load(Ordering::SeqCst));
+    let result = wrap_err_with!(iter, must!(text_token!("bar")), msg_fn);
+    assert!(called.load(Ordering::SeqCst));
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Expected keyword at 0");
+        assert!(e.get_cause().is_some());
+    }
+}
+
+#[test]
+fn test_must_msg() {
+    let iter = StrIter::new("foo");
+    let result = must_msg!(iter, text_token!("foo"), "Expected foo");
+    assert!(result.is_complete());
+    let result = must_msg!(iter, ascii_digit, "Expected a digit");
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Expected a digit");
+        assert_eq!(e.get_offset(), 0);
+        let cause = e.get_cause().unwrap();
+        assert_eq!(cause.get_msg(), "Not an digit character");
+        assert!(cause.get_cause().is_none());
+    }
+    let result = must_msg!(iter, must!(ascii_digit), "Expected a digit");
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Not an digit character");
+    } else {
+        panic!("must_msg! did not abort");
+    }
+}
+
+fn keyword_arg<'a>(i: StrIter<'a>, kw: &'static str) -> Result<StrIter<'a>, &'static str> {
+    match text_token!(i, kw) {
+        Result::Complete(i, _) => Result::Complete(i, kw),
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
+#[test]
+fn test_cut() {
+    let iter = StrIter::new("foo");
+    assert!(cut!(iter, text_token!("foo")).is_complete());
+    assert!(cut!(iter, text_token!("bar")).is_abort());
+    assert!(cut!(iter, ascii_digit).is_abort());
+    assert!(cut!(iter, keyword_arg("foo")).is_complete());
+    assert!(cut!(iter, keyword_arg("bar")).is_abort());
+}
+
+#[test]
+fn test_cut_in_either() {
+    let iter = StrIter::new("let 1");
+    let result = either!(
+        iter,
+        do_each!(
+            _ => text_token!("let "),
+            name => cut!(consume_all!(ascii_alpha)),
+            _ => cut!(ascii_alpha),
+            (name)
+        ),
+        text_token!("let")
+    );
+    assert!(result.is_abort());
+    let result = either!(
+        iter,
+        do_each!(
+            _ => text_token!("let "),
+            _ => ascii_alpha,
+            ("x")
+        ),
+        text_token!("let")
+    );
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_parser_trait() {
+    type DynParser = Box<dyn Fn(StrIter<'static>) -> Result<StrIter<'static>, u8>>;
+    let alpha: DynParser = Box::new(ascii_alpha);
+    let digit: DynParser = Box::new(ascii_digit);
+    let alnum = alpha.or(digit).map(|b| b as char);
+    let result = alnum.parse(StrIter::new("1"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, '1');
+        assert_eq!(i.get_offset(), 1);
+    }
+    assert!(alnum.parse(StrIter::new(";")).is_fail());
+
+    let repeated = ascii_digit.and_then(|b: u8| {
+        move |i: StrIter<'static>| match ascii_digit(i) {
+            Result::Complete(i, o) if o == b => Result::Complete(i, o),
+            Result::Complete(i, _) => Result::Fail(Error::new("Digits differ", Box::new(i))),
+            other => other,
+        }
+    });
+    assert!(repeated.parse(StrIter::new("11")).is_complete());
+    assert!(repeated.parse(StrIter::new("12")).is_fail());
+
+    let foo = |i: StrIter<'static>| must!(i, text_token!("foo"));
+    let bar = |i: StrIter<'static>| text_token!(i, "bar");
+    assert!(foo.or(bar).parse(StrIter::new("bar")).is_abort());
+}
+
+#[test]
+fn test_boxed_parser() {
+    let keyword = "let";
+    let parsers: Vec<BoxedParser<StrIter<'static>, &str>> = vec![
+        (|i: StrIter<'static>| text_token!(i, "foo")).boxed(),
+        (|i: StrIter<'static>| consume_all0!(i, ascii_digit)).boxed(),
+        ascii_alpha.map(|_| "alpha").boxed(),
+        BoxedParser::new(move |i: StrIter<'static>| text_token!(i, keyword)),
+    ];
+    let expected = [
+        ("foo", Some("foo")),
+        ("123", Some("123")),
+        ("x", Some("alpha")),
+        ("let", Some("let")),
+    ];
+    for (parser, (input, output)) in parsers.iter().zip(expected.iter()) {
+        match parser.parse(StrIter::new(input)) {
+            Result::Complete(_, o) => assert_eq!(Some(o), *output),
+            _ => assert_eq!(None, *output),
+        }
+    }
+    assert!(parsers[0].parse(StrIter::new("bar")).is_fail());
+}
+
+make_fn!(
+    nested_parens<'a, StrIter<'a>, usize>,
+    do_each!(
+        _ => text_token!("("),
+        inner => optional!(nested_parens),
+        _ => must!(text_token!(")")),
+        (inner.unwrap_or(0) + 1)
+    )
+);
+
+#[test]
+fn test_recursive_rule() {
+    let result = nested_parens(StrIter::new("((()))"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, depth) = result {
+        assert_eq!(depth, 3);
+        assert_eq!(i.get_offset(), 6);
+    }
+    assert!(nested_parens(StrIter::new("(()")).is_abort());
+    let result = either!(
+        StrIter::new("(())"),
+        nested_parens,
+        ref_parser!(|i| Result::Complete(i, 0))
+    );
+    if let Result::Complete(_, depth) = result {
+        assert_eq!(depth, 2);
+    } else {
+        panic!("either! did not complete");
+    }
+}
+
+#[test]
+fn test_ref_parser() {
+    struct Grammar<'a> {
+        item: BoxedParser<'a, StrIter<'a>, u8>,
+    }
+    let grammar = Grammar {
+        item: ascii_digit.boxed(),
+    };
+    let result = repeat!(StrIter::new("12a"), ref_parser!(grammar.item));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec![b'1', b'2']);
+        assert_eq!(i.get_offset(), 2);
+    }
+    let result = ref_parser!(StrIter::new("1"), nested_parens);
+    assert!(result.is_fail());
+}
+
+static EXPENSIVE_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn expensive_word(i: StrIter<'_>) -> Result<StrIter<'_>, &str> {
+    EXPENSIVE_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    consume_all!(i, ascii_alpha)
+}
+
+#[test]
+fn test_memoize() {
+    let cache = PackratContext::new();
+    let iter = StrIter::new("foo bar;");
+    let result = either!(
+        iter,
+        do_each!(
+            first => memoize!(cache, expensive_word),
+            _ => text_token!(" "),
+            _ => memoize!(cache, expensive_word),
+            _ => text_token!("."),
+            (first)
+        ),
+        do_each!(
+            first => memoize!(cache, expensive_word),
+            _ => text_token!(" "),
+            _ => memoize!(cache, expensive_word),
+            _ => text_token!(";"),
+            (first)
+        )
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 8);
+    }
+    assert_eq!(EXPENSIVE_CALLS.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert_eq!(cache.len(), 2);
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[derive(Debug, PartialEq)]
+enum Arith {
+    Num(i64),
+    Sub(Box<Arith>, Box<Arith>),
+}
+
+fn arith_num(i: StrIter<'_>) -> Result<StrIter<'_>, Arith> {
+    tagged_either!(i, { Arith::Num => signed_integer })
+}
+
+#[test]
+fn test_left_assoc() {
+    let iter = StrIter::new("1-2-3");
+    let result = left_assoc!(iter, signed_integer, text_token!("-"), |l, _, r| l - r);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, -4);
+        assert_eq!(i.get_offset(), 5);
+    }
+
+    let iter = StrIter::new("1-2-3-");
+    let result = left_assoc!(iter, arith_num, text_token!("-"), |l, _, r| Arith::Sub(
+        Box::new(l),
+        Box::new(r)
+    ));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        let expected = Arith::Sub(
+            Box::new(Arith::Sub(Box::new(Arith::Num(1)), Box::new(Arith::Num(2)))),
+            Box::new(Arith::Num(3)),
+        );
+        assert_eq!(i.get_offset(), 5);
+        assert_eq!(o, expected);
+    }
+
+    let iter = StrIter::new("x-1");
+    assert!(left_assoc!(iter, signed_integer, text_token!("-"), |l, _, r| l - r).is_fail());
+}
+
+fn num_string(i: StrIter<'_>) -> Result<StrIter<'_>, String> {
+    match signed_integer(i) {
+        Result::Complete(i, n) => Result::Complete(i, n.to_string()),
+        Result::Incomplete(ctx) => Result::Incomplete(ctx),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+    }
+}
+
+#[test]
+fn test_pratt_precedence() {
+    let iter = StrIter::new("1+2*3");
+    let result = pratt!(iter, signed_integer, {
+        text_token!("+") => (1, Assoc::Left, |l, r| l + r),
+        text_token!("*") => (2, Assoc::Left, |l, r| l * r),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, 7);
+        assert_eq!(i.get_offset(), 5);
+    }
+
+    let iter = StrIter::new("1*2+3*4-5;");
+    let result = pratt!(iter, num_string, {
+        text_token!("+") => (1, Assoc::Left, |l, r| format!("({}+{})", l, r)),
+        text_token!("-") => (1, Assoc::Left, |l, r| format!("({}-{})", l, r)),
+        text_token!("*") => (2, Assoc::Left, |l, r| format!("({}*{})", l, r)),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "(((1*2)+(3*4))-5)");
+        assert_eq!(i.get_offset(), 9);
+    }
+
+    let iter = StrIter::new("1+");
+    let result = pratt!(iter, signed_integer, {
+        text_token!("+") => (1, Assoc::Left, |l, r| l + r),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, 1);
+        assert_eq!(i.get_offset(), 1);
+    }
+}
+
+#[test]
+fn test_pratt_associativity() {
+    let iter = StrIter::new("2^3^2");
+    let result = pratt!(iter, signed_integer, {
+        text_token!("^") => (3, Assoc::Right, |l: i64, r: i64| l.pow(r as u32)),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, 512);
+    }
+    let result = pratt!(iter, signed_integer, {
+        text_token!("^") => (3, Assoc::Left, |l: i64, r: i64| l.pow(r as u32)),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, 64);
+    }
+
+    let iter = StrIter::new("1-2^2^2*3");
+    let result = pratt!(iter, num_string, {
+        text_token!("-") => (1, Assoc::Left, |l, r| format!("({}-{})", l, r)),
+        text_token!("*") => (2, Assoc::Left, |l, r| format!("({}*{})", l, r)),
+        text_token!("^") => (3, Assoc::Right, |l, r| format!("({}^{})", l, r)),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, "(1-((2^(2^2))*3))");
+    } else {
+        panic!("pratt! did not complete");
+    }
+}
+
+#[test]
+fn test_between_balanced() {
+    let iter = StrIter::new("{ a { b { c } } d } rest");
+    let result: Result<StrIter, &str> = between_balanced!(iter, b'{', b'}');
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, " a { b { c } } d ");
+        assert_eq!(i.get_offset(), 19);
+    }
+
+    let iter = SliceIter::new("()".as_bytes());
+    let result: Result<SliceIter<u8>, &[u8]> = between_balanced!(iter, b'(', b')');
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert!(o.is_empty());
+        assert_eq!(i.get_offset(), 2);
+    }
+
+    let iter = StrIter::new("{ a { b }");
+    let result: Result<StrIter, &str> = between_balanced!(iter, b'{', b'}');
+    assert!(result.is_incomplete());
+
+    let iter = StrIter::new("a{}");
+    let result: Result<StrIter, &str> = between_balanced!(iter, b'{', b'}');
+    assert!(result.is_fail());
+    let iter = StrIter::new("");
+    let result: Result<StrIter, &str> = between_balanced!(iter, b'{', b'}');
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_must_fails() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = must!(iter, will_fail);
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_must_succeed() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = must!(iter, parse_byte);
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_trap_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = trap!(iter, must!(will_fail));
+    assert!(result.is_fail(), "{:?}", result);
+}
+
+#[test]
+fn test_trap_incomplete() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = trap!(iter, will_not_complete);
+    assert!(result.is_incomplete(), "{:?}", result);
+}
+
+#[test]
+fn test_trap_fail() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = trap!(iter, will_fail);
+    assert!(result.is_fail(), "{:?}", result);
+}
+
+#[test]
+fn test_trap_complete() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = trap!(iter, parse_byte);
+    assert!(result.is_complete(), "{:?}", result);
+}
+
+#[test]
+#[allow(clippy::clone_on_copy)]
+fn test_must_complete() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let iter_fail = iter.clone();
+    let mut result = must_complete!(iter, "AHHH".to_string(), will_not_complete);
+    assert!(result.is_abort());
+    result = must_complete!(iter_fail, "AHHH".to_string(), will_fail);
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_do_each() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = do_each!(iter,
+        b1 => parse_byte,
+        _ => parse_byte,
+        b3 => parse_byte,
+        (b1, b3)
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(b'f', o.0);
+        assert_eq!(b'o', o.1);
+    } else {
+        panic!("did not get a tuple of 2 items");
+    }
+}
+
+#[test]
+fn test_do_each_input_and_token() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = do_each!(iter,
+        _ => input!(),
+        token => text_token!("foo"),
+        (token)
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!("foo", o);
+    } else {
+        panic!("did not get our token");
+    }
+}
+
+#[test]
+fn test_captures() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = captures!(iter, {
+        first: parse_byte,
+        rest: text_token!("oo"),
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(i, (first, rest)) = result {
+        assert_eq!(first, b'f');
+        assert_eq!(rest, "oo");
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_with_span_str_iter() {
+    let input_str = "foo123;";
+    let iter = StrIter::new(input_str);
+    let result = with_span!(iter, repeat!(ascii_alphanumeric));
+    assert!(result.is_complete());
+    if let Result::Complete(i, (o, span)) = result {
+        assert_eq!(o.len(), 6);
+        assert_eq!(span, "foo123");
+        assert_eq!(i.get_offset(), 6);
+    }
+}
+
+#[test]
+fn test_with_span_slice_iter() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = with_span!(iter, parse_byte);
+    assert!(result.is_complete());
+    if let Result::Complete(_, (o, span)) = result {
+        assert_eq!(o, b'f');
+        assert_eq!(span, b"f");
+    }
+}
+
+#[test]
+fn test_rest_len() {
+    let input_str = "abc";
+    let mut iter = StrIter::new(input_str);
+    iter.next();
+    let result = rest_len!(iter);
+    assert!(result.is_complete());
+    if let Result::Complete(i, len) = result {
+        assert_eq!(len, 2);
+        assert_eq!(i.get_offset(), 1);
+    }
+}
+
+#[test]
+fn test_rest_len_chars() {
+    let iter = CharIter::new("héllo");
+    let result = rest_len!(iter);
+    assert!(result.is_complete());
+    if let Result::Complete(_, len) = result {
+        assert_eq!(len, 5);
+    }
+}
+
+#[test]
+fn test_span_between() {
+    let input_str = "foo;";
+    let iter = StrIter::new(input_str);
+    let result = do_each!(iter,
+        start => input!(),
+        _ => text_token!("f"),
+        _ => text_token!("oo"),
+        end => input!(),
+        (span_between!(end, start.get_offset(), end.get_offset()))
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo");
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_either_idents() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = either!(iter, will_fail, will_fail, parse_three);
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!("foo".to_string(), o);
+    } else {
+        panic!("Didn't not successfully match");
+    }
+}
+
+#[test]
+fn test_either_macros() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = either!(iter, run!(will_fail), run!(will_fail), run!(parse_three));
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!("foo".to_string(), o);
+    } else {
+        panic!("Didn't successfully match");
+    }
+}
+
+#[test]
+fn test_either_fail() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = either!(iter, run!(will_fail), run!(will_fail));
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_either_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = either!(iter, must!(will_fail), parse_three, run!(will_fail));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_optional_some() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = optional!(iter, parse_byte);
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(b'f', o.unwrap());
+    } else {
+        panic!("optional! did not complete");
+    }
+}
+
+#[test]
+fn test_optional_none() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = optional!(iter, will_fail);
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert!(o.is_none(), "output was not none");
+    } else {
+        panic!("optional! did not complete");
+    }
+}
+
+#[test]
+fn test_optional_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = optional!(iter, must!(will_fail));
+    assert!(result.is_abort(), "optional did not abort");
+}
+
+#[test]
+fn test_optional_seek_none() {
+    let input_str = "foo";
+    let iter = StrIter::new(input_str);
+    // The failure leaves its context past the consumed "fo".
+    let result: Result<StrIter, ()> = do_each!(iter,
+        _ => text_token!("fo"),
+        _ => text_token!("x"),
+        ()
+    );
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_context().get_offset(), 2);
+    } else {
+        panic!("do_each! did not fail");
+    }
+    let result = optional_seek!(
+        iter,
+        do_each!(
+            _ => text_token!("fo"),
+            _ => text_token!("x"),
+            ()
+        )
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert!(o.is_none());
+        assert_eq!(i.get_offset(), 0);
+    }
+}
+
+#[test]
+fn test_optional_seek_some() {
+    let input_str = "foo";
+    let iter = StrIter::new(input_str);
+    let result = optional_seek!(iter, text_token!("fo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, Some("fo"));
+        assert_eq!(i.get_offset(), 2);
+    }
+}
+
+#[test]
+fn test_repeat() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = repeat!(iter, parse_byte);
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(3, o.len());
+        assert_eq!(b'f', o[0]);
+        assert_eq!(b'o', o[1]);
+        assert_eq!(b'o', o[2]);
+    } else {
+        panic!("repeat did not parse succesfully");
+    }
+}
+
+#[test]
+fn test_repeat_fail() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = repeat!(iter, will_fail);
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(0, o.len());
+    } else {
+        panic!("repeat did not parse succesfully");
     }
 }
 
 #[test]
 fn test_repeat_abort() {
     let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = repeat!(iter, must!(will_fail));
-    assert!(result.is_abort());
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = repeat!(iter, must!(will_fail));
+    assert!(result.is_abort());
+}
+
+fn header<'a>(i: StrIter<'a>) -> Result<StrIter<'a>, &'a str> {
+    do_each!(i,
+        h => text_token!("H"),
+        _ => text_token!("\n"),
+        (h)
+    )
+}
+
+fn body_line<'a>(i: StrIter<'a>) -> Result<StrIter<'a>, &'a str> {
+    do_each!(i,
+        line => consume_all!(ascii_alpha),
+        _ => text_token!("\n"),
+        (line)
+    )
+}
+
+#[test]
+fn test_repeat_indexed() {
+    let input_str = "H\nfoo\nbar\n1\n";
+    let iter = StrIter::new(input_str);
+    let result = repeat_indexed!(iter, |idx| -> fn(
+        StrIter<'static>,
+    )
+        -> Result<StrIter<'static>, &'static str> {
+        if idx == 0 {
+            header
+        } else {
+            body_line
+        }
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec!["H", "foo", "bar"]);
+        assert_eq!(i.get_offset(), 10);
+    }
+}
+
+#[test]
+fn test_repeat_indexed_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = repeat_indexed!(iter, |_| |i| must!(i, will_fail));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_fold_many() {
+    let input_str = "123;";
+    let iter = StrIter::new(input_str);
+    let result = fold_many!(
+        iter,
+        0u32,
+        |acc, b: u8| acc + (b - b'0') as u32,
+        ascii_digit
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, total) = result {
+        assert_eq!(total, 6);
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_fold_many_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = fold_many!(iter, 0, |acc, _: String| acc + 1, must!(will_fail));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_count_matches() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = count_matches!(iter, parse_byte);
+    assert!(result.is_complete());
+    if let Result::Complete(i, count) = result {
+        assert_eq!(count, 3);
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_count_matches_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = count_matches!(iter, must!(will_fail));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_until() {
+    let input_str = "foo; ";
+    let iter = StrIter::new(input_str);
+    let result = until!(iter, text_token!("; "));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o.len(), 3);
+        assert_eq!(o, "foo");
+    }
+}
+
+#[test]
+fn test_until_with_function_composition() {
+    let input_str = "foo ";
+    let iter = StrIter::new(input_str);
+    let result = until!(iter, ascii_ws);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o.len(), 3);
+        assert_eq!(o, "foo");
+    }
+}
+
+#[test]
+fn test_count_until() {
+    let iter = StrIter::new("foo;");
+    let result = count_until!(iter, text_token!(";"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, count) = result {
+        assert_eq!(count, 3);
+        assert_eq!(i.get_offset(), 3);
+        assert!(text_token!(i, ";").is_complete());
+    }
+    let iter = StrIter::new(";");
+    let result = count_until!(iter, text_token!(";"));
+    assert!(result.is_complete());
+    if let Result::Complete(_, count) = result {
+        assert_eq!(count, 0);
+    }
+    let iter = StrIter::new("foo");
+    assert!(count_until!(iter, text_token!(";")).is_incomplete());
+}
+
+#[test]
+fn test_until_abort() {
+    let input_str = "foo ";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = until!(iter, must!(will_fail));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_until_incomplete() {
+    let input_str = "foo;";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = until!(iter, text_token!("; "));
+    assert!(result.is_incomplete());
+    if let Result::Incomplete(ctx) = result {
+        let ctx: SliceIter<u8> = ctx;
+        assert_eq!(ctx.get_offset(), 4);
+        assert_eq!(ctx.span(SpanRange::RangeTo(..4)), input_str.as_bytes());
+    }
+}
+
+#[test]
+fn test_discard_success() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = discard!(iter, text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(_, o) = result {
+        assert_eq!(o, ());
+    }
+}
+
+#[test]
+fn test_discard_fail() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = discard!(iter, will_fail);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_discard_abort() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = discard!(iter, must!(will_fail));
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_discard_incomplete() {
+    let input_str = "foo";
+    let iter = SliceIter::new(input_str.as_bytes());
+    let result = discard!(iter, will_not_complete);
+    assert!(result.is_incomplete());
+}
+
+#[test]
+fn test_eoi_success() {
+    let input_str = "";
+    let iter = StrIter::new(input_str);
+    let result = eoi(iter);
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_eoi_fail() {
+    let input_str = " ";
+    let iter = StrIter::new(input_str);
+    let result = eoi(iter);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_ascii_ws_space() {
+    let input_str = " ";
+    let iter = StrIter::new(input_str);
+    let result = ascii_ws(iter);
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_ascii_ws_tab() {
+    let input_str = "\t";
+    let iter = StrIter::new(input_str);
+    let result = ascii_ws(iter);
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_ascii_ws_newline() {
+    let input_str = "\n";
+    let iter = StrIter::new(input_str);
+    let result = ascii_ws(iter);
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_ascii_ws_carriage_return() {
+    let input_str = "\r";
+    let iter = StrIter::new(input_str);
+    let result = ascii_ws(iter);
+    assert!(result.is_complete());
+}
+
+#[test]
+fn test_position_tracking_striter() {
+    let input_str = "\n";
+    let mut iter = StrIter::new(input_str);
+    assert_eq!(iter.line(), 1);
+    assert_eq!(iter.column(), 1);
+    iter.next();
+    assert_eq!(iter.line(), 2);
+    assert_eq!(iter.column(), 1);
+    let pos_result: Result<StrIter, StrIter> = input!(iter);
+    assert!(pos_result.is_complete());
+    if let Result::Complete(_, i) = pos_result {
+        assert_eq!(i.line(), 2);
+        assert_eq!(i.column(), 1);
+    }
+}
+
+#[test]
+fn test_located() {
+    let input_str = "foo\n  bar";
+    let iter = StrIter::new(input_str);
+    let result = do_each!(iter,
+        _ => text_token!("foo\n  "),
+        bar => located!(text_token!("bar")),
+        (bar)
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(_, (o, (line, column))) = result {
+        assert_eq!(o, "bar");
+        assert_eq!(line, 2);
+        assert_eq!(column, 3);
+    }
+}
+
+#[test]
+fn test_error_get_position() {
+    let input_str = "foo\nbar";
+    let iter = StrIter::new(input_str);
+    let result = do_each!(iter,
+        _ => text_token!("foo\nb"),
+        _ => text_token!("z"),
+        ()
+    );
+    assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_position(), (2, 2));
+    }
+}
+
+fn position_of<P: Positioned>(p: &P) -> (usize, usize) {
+    (p.line(), p.column())
+}
+
+#[allow(deprecated)]
+fn deprecated_position_of<P: super::TextPositionTracker>(p: &P) -> (usize, usize) {
+    (p.line(), p.column())
+}
+
+#[test]
+fn test_positioned_trait_name() {
+    let mut iter = StrIter::new("a\nb");
+    iter.next();
+    iter.next();
+    assert_eq!(position_of(&iter), (2, 1));
+    assert_eq!(deprecated_position_of(&iter), (2, 1));
+    let iter = CharIter::new("a");
+    assert_eq!(position_of(&iter), (1, 1));
+}
+
+#[test]
+fn test_separated_good() {
+    let input_str = "foo,foo,foo";
+    let iter = StrIter::new(input_str);
+    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(_, list) = result {
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0], "foo");
+        assert_eq!(list[1], "foo");
+        assert_eq!(list[2], "foo");
+    }
+}
+
+#[test]
+fn test_separated_single_item() {
+    let input_str = "foo";
+    let iter = StrIter::new(input_str);
+    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(_, list) = result {
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0], "foo");
+    }
+}
+
+#[test]
+fn test_separated_empty_list() {
+    let input_str = "";
+    let iter = StrIter::new(input_str);
+    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_separated_bad() {
+    let input_str = "bar foo,foo";
+    let iter = StrIter::new(input_str);
+    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_separated_trailing_comma() {
+    let input_str = "foo,foo,foo,";
+    let iter = StrIter::new(input_str);
+    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0], "foo");
+        assert_eq!(list[1], "foo");
+        assert_eq!(list[2], "foo");
+        assert!(text_token!(i, ",").is_complete());
+    }
+}
+
+#[test]
+fn test_separated_trailing_with_separator() {
+    let input_str = "foo,foo,";
+    let iter = StrIter::new(input_str);
+    let result = separated_trailing!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 2);
+        assert_eq!(i.get_offset(), 8);
+        assert!(eoi(i).is_complete());
+    }
+}
+
+#[test]
+fn test_separated_trailing_without_separator() {
+    let input_str = "foo,foo;";
+    let iter = StrIter::new(input_str);
+    let result = separated_trailing!(iter, text_token!(","), text_token!("foo"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 2);
+        assert_eq!(i.get_offset(), 7);
+        assert!(text_token!(i, ";").is_complete());
+    }
+}
+
+#[test]
+fn test_separated_fold() {
+    let input_str = "1,2,3";
+    let iter = StrIter::new(input_str);
+    let result = separated_fold!(iter, text_token!(","), ascii_digit, 0u32, |acc, b: u8| {
+        acc + (b - b'0') as u32
+    });
+    assert!(result.is_complete());
+    if let Result::Complete(i, total) = result {
+        assert_eq!(total, 6);
+        assert!(eoi(i).is_complete());
+    }
+}
+
+#[test]
+fn test_separated_fold_empty_list() {
+    let input_str = "";
+    let iter = StrIter::new(input_str);
+    let result = separated_fold!(iter, text_token!(","), ascii_digit, 0u32, |acc, b: u8| {
+        acc + (b - b'0') as u32
+    });
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_ascii_alphanumeric() {
+    let input_str = "a1";
+    let iter = StrIter::new(input_str);
+    let result = repeat!(iter, ascii_alphanumeric);
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0], b'a');
+        assert_eq!(list[1], b'1');
+        assert!(eoi(i).is_complete());
+    }
+}
+
+#[test]
+fn test_ascii_alphanumeric_fail() {
+    let input_str = "-";
+    let iter = StrIter::new(input_str);
+    let result = ascii_alphanumeric(iter);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_ascii_digit() {
+    let input_str = "12";
+    let iter = StrIter::new(input_str);
+    let result = repeat!(iter, ascii_digit);
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0], b'1');
+        assert_eq!(list[1], b'2');
+        assert!(eoi(i).is_complete());
+    }
+}
+
+#[test]
+fn test_ascii_digit_fail() {
+    let input_str = "-";
+    let iter = StrIter::new(input_str);
+    let result = ascii_digit(iter);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_ascii_hexdigit() {
+    let input_str = "aF9";
+    let iter = StrIter::new(input_str);
+    let result = repeat!(iter, ascii_hexdigit);
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0], b'a');
+        assert_eq!(list[1], b'F');
+        assert_eq!(list[2], b'9');
+        assert!(eoi(i).is_complete());
+    }
+}
+
+#[test]
+fn test_ascii_hexdigit_fail() {
+    let input_str = "g";
+    let iter = StrIter::new(input_str);
+    let result = ascii_hexdigit(iter);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_ascii_alpha() {
+    let input_str = "ab";
+    let iter = StrIter::new(input_str);
+    let result = repeat!(iter, ascii_alpha);
+    assert!(result.is_complete());
+    if let Result::Complete(i, list) = result {
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0], b'a');
+        assert_eq!(list[1], b'b');
+        assert!(eoi(i).is_complete());
+    }
+}
+
+#[test]
+fn test_ascii_alpha_fail() {
+    let input_str = "1";
+    let iter = StrIter::new(input_str);
+    let result = ascii_alpha(iter);
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_consume_all() {
+    let input_str = "foo;";
+    let iter = StrIter::new(input_str);
+    let result = consume_all!(iter, ascii_alpha);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o, "foo");
+        assert!(text_token!(i, ";").is_complete());
+    }
+}
+
+#[test]
+fn test_consume_all0() {
+    let iter = StrIter::new("foo");
+    assert!(consume_all!(iter, not!(text_token!(";"))).is_incomplete());
+    let result = consume_all0!(iter, not!(text_token!(";")));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o, "foo");
+    }
+    let result = consume_all0!(iter, ascii_alpha);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o, "foo");
+    }
+    let result = consume_all0!(StrIter::new(""), ascii_alpha);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 0);
+        assert_eq!(o, "");
+    }
+}
+
+#[test]
+fn test_until_and_consume_all_spans() {
+    let iter = SliceIter::new("foo; ".as_bytes());
+    let result = until!(iter, text_token!("; "));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o, b"foo");
+    }
+    let result = consume_all!(iter, ascii_alpha);
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o, b"foo");
+    }
+    let iter = CharIter::new("λx;");
+    let result = until!(iter, one_of_char!(";"));
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(i.get_offset(), 3);
+        assert_eq!(o, "λx");
+    }
 }
 
 #[test]
-fn test_until() {
-    let input_str = "foo; ";
-    let iter = StrIter::new(input_str);
-    let result = until!(iter, text_token!("; "));
+fn test_one_of_char() {
+    let input_str = "λx";
+    let iter = CharIter::new(input_str);
+    let result = one_of_char!(iter, "αλ");
     assert!(result.is_complete());
     if let Result::Complete(i, o) = result {
-        assert_eq!(i.get_offset(), 3);
-        assert_eq!(o.len(), 3);
-        assert_eq!(o, "foo");
+        assert_eq!(o, 'λ');
+        assert_eq!(i.get_offset(), 2);
+        assert!(one_of_char!(i, "αλ").is_fail());
     }
 }
 
 #[test]
-fn test_until_with_function_composition() {
-    let input_str = "foo ";
+fn test_one_of_char_eoi() {
+    let input_str = "";
+    let iter = CharIter::new(input_str);
+    let result = one_of_char!(iter, "αλ");
+    assert!(result.is_fail());
+}
+
+#[test]
+fn test_many1_until() {
+    let input_str = "abc]";
     let iter = StrIter::new(input_str);
-    let result = until!(iter, ascii_ws);
+    let result = many1_until!(iter, ascii_alpha, text_token!("]"));
     assert!(result.is_complete());
-    if let Result::Complete(i, o) = result {
-        assert_eq!(i.get_offset(), 3);
-        assert_eq!(o.len(), 3);
-        assert_eq!(o, "foo");
+    if let Result::Complete(i, (items, term)) = result {
+        assert_eq!(items, vec![b'a', b'b', b'c']);
+        assert_eq!(term, "]");
+        assert!(eoi(i).is_complete());
     }
 }
 
 #[test]
-fn test_until_abort() {
-    let input_str = "foo ";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = until!(iter, must!(will_fail));
-    assert!(result.is_abort());
+fn test_many1_until_no_items() {
+    let input_str = "]";
+    let iter = StrIter::new(input_str);
+    let result = many1_until!(iter, ascii_alpha, text_token!("]"));
+    assert!(result.is_fail());
 }
 
-#[test]
-fn test_until_incomplete() {
-    let input_str = "foo;";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = until!(iter, text_token!("; "));
-    assert!(result.is_incomplete());
+fn is_even(digits: &[u8]) -> std::result::Result<(), String> {
+    match digits.last() {
+        Some(b'0') | Some(b'2') | Some(b'4') | Some(b'6') | Some(b'8') => Ok(()),
+        _ => Err("Expected an even number".to_string()),
+    }
 }
 
 #[test]
-fn test_discard_success() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = discard!(iter, text_token!("foo"));
+fn test_verify_full() {
+    let input_str = "124";
+    let iter = StrIter::new(input_str);
+    let result = verify_full!(iter, repeat!(ascii_digit), is_even);
     assert!(result.is_complete());
-    if let Result::Complete(_, o) = result {
-        assert_eq!(o, ());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, vec![b'1', b'2', b'4']);
+        assert_eq!(i.get_offset(), 3);
     }
 }
 
 #[test]
-fn test_discard_fail() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = discard!(iter, will_fail);
-    assert!(result.is_fail());
+fn test_verify_full_validation_abort() {
+    let input_str = "123";
+    let iter = StrIter::new(input_str);
+    let result = verify_full!(iter, repeat!(ascii_digit), is_even);
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Expected an even number");
+    }
 }
 
 #[test]
-fn test_discard_abort() {
-    let input_str = "foo";
-    let iter = SliceIter::new(input_str.as_bytes());
-    let result = discard!(iter, must!(will_fail));
+fn test_verify_full_trailing_abort() {
+    let input_str = "124x";
+    let iter = StrIter::new(input_str);
+    let result = verify_full!(iter, repeat!(ascii_digit), is_even);
     assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_offset(), 3);
+    }
 }
 
 #[test]
-fn test_discard_incomplete() {
-    let input_str = "foo";
+fn test_satisfy() {
+    let input_str = "xy";
     let iter = SliceIter::new(input_str.as_bytes());
-    let result = discard!(iter, will_not_complete);
-    assert!(result.is_incomplete());
+    let result = satisfy!(iter, |b: &u8| *b == b'x');
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(*o, b'x');
+        assert_eq!(i.get_offset(), 1);
+        assert!(satisfy!(i, |b: &u8| *b == b'x').is_fail());
+    }
 }
 
 #[test]
-fn test_eoi_success() {
+fn test_satisfy_eoi() {
     let input_str = "";
     let iter = StrIter::new(input_str);
-    let result = eoi(iter);
-    assert!(result.is_complete());
+    let result = satisfy!(iter, |b: &u8| *b == b'x');
+    assert!(result.is_fail());
 }
 
 #[test]
-fn test_eoi_fail() {
-    let input_str = " ";
+fn test_peek_count() {
+    let input_str = "    x";
     let iter = StrIter::new(input_str);
-    let result = eoi(iter);
-    assert!(result.is_fail());
+    let result = peek_count!(iter, |b: &u8| *b == b' ');
+    assert!(result.is_complete());
+    if let Result::Complete(i, count) = result {
+        assert_eq!(count, 4);
+        assert_eq!(i.get_offset(), 0);
+    }
 }
 
 #[test]
-fn test_ascii_ws_space() {
-    let input_str = " ";
+fn test_peek_count_to_end() {
+    let input_str = "  ";
     let iter = StrIter::new(input_str);
-    let result = ascii_ws(iter);
+    let result = peek_count!(iter, |b: &u8| *b == b' ');
     assert!(result.is_complete());
+    if let Result::Complete(_, count) = result {
+        assert_eq!(count, 2);
+    }
 }
 
 #[test]
-fn test_ascii_ws_tab() {
-    let input_str = "\t";
-    let iter = StrIter::new(input_str);
-    let result = ascii_ws(iter);
-    assert!(result.is_complete());
+fn test_signed_integer() {
+    for (input_str, expected) in [("-42", -42), ("+7", 7), ("0", 0)].iter().cloned() {
+        let iter = StrIter::new(input_str);
+        let result = signed_integer(iter);
+        assert!(result.is_complete());
+        if let Result::Complete(i, n) = result {
+            assert_eq!(n, expected);
+            assert!(eoi(i).is_complete());
+        }
+    }
 }
 
 #[test]
-fn test_ascii_ws_newline() {
-    let input_str = "\n";
+fn test_signed_integer_trailing_input() {
+    let input_str = "123abc";
     let iter = StrIter::new(input_str);
-    let result = ascii_ws(iter);
+    let result = signed_integer(iter);
     assert!(result.is_complete());
+    if let Result::Complete(i, n) = result {
+        assert_eq!(n, 123);
+        assert_eq!(i.get_offset(), 3);
+        assert!(text_token!(i, "abc").is_complete());
+    }
 }
 
 #[test]
-fn test_ascii_ws_carriage_return() {
-    let input_str = "\r";
-    let iter = StrIter::new(input_str);
-    let result = ascii_ws(iter);
-    assert!(result.is_complete());
+fn test_signed_integer_fail() {
+    let iter = StrIter::new("-abc");
+    assert!(signed_integer(iter).is_fail());
 }
 
 #[test]
-fn test_position_tracking_striter() {
-    let input_str = "\n";
-    let mut iter = StrIter::new(input_str);
-    assert_eq!(iter.line(), 1);
-    assert_eq!(iter.column(), 1);
-    iter.next();
-    assert_eq!(iter.line(), 2);
-    assert_eq!(iter.column(), 1);
-    let pos_result: Result<StrIter, StrIter> = input!(iter);
-    assert!(pos_result.is_complete());
-    if let Result::Complete(_, i) = pos_result {
-        assert_eq!(i.line(), 2);
-        assert_eq!(i.column(), 1);
+fn test_signed_integer_overflow() {
+    let iter = StrIter::new("99999999999999999999");
+    assert!(signed_integer(iter).is_abort());
+}
+
+#[test]
+#[allow(clippy::approx_constant)]
+fn test_floating_point() {
+    for (input_str, expected) in [
+        ("3.14", 3.14),
+        ("-0.5e10", -0.5e10),
+        (".5", 0.5),
+        ("7", 7.0),
+    ]
+    .iter()
+    .cloned()
+    {
+        let iter = StrIter::new(input_str);
+        let result = floating_point(iter);
+        assert!(result.is_complete());
+        if let Result::Complete(i, n) = result {
+            assert_eq!(n, expected);
+            assert!(eoi(i).is_complete());
+        }
     }
 }
 
 #[test]
-fn test_separated_good() {
-    let input_str = "foo,foo,foo";
+fn test_floating_point_trailing_input() {
+    let iter = StrIter::new("1.5e");
+    let result = floating_point(iter);
+    assert!(result.is_complete());
+    if let Result::Complete(i, n) = result {
+        assert_eq!(n, 1.5);
+        assert_eq!(i.get_offset(), 3);
+    }
+}
+
+#[test]
+fn test_floating_point_fail() {
+    let iter = StrIter::new("abc");
+    assert!(floating_point(iter).is_fail());
+    let iter = StrIter::new("-.");
+    assert!(floating_point(iter).is_fail());
+}
+
+#[test]
+fn test_key_path() {
+    let input_str = "foo.bar[2].baz";
     let iter = StrIter::new(input_str);
-    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    let result = key_path!(iter);
     assert!(result.is_complete());
-    if let Result::Complete(_, list) = result {
-        assert_eq!(list.len(), 3);
-        assert_eq!(list[0], "foo");
-        assert_eq!(list[1], "foo");
-        assert_eq!(list[2], "foo");
+    if let Result::Complete(i, path) = result {
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[0], PathSegment::Key("foo"));
+        assert_eq!(path[1], PathSegment::Key("bar"));
+        assert_eq!(path[2], PathSegment::Index(2));
+        assert_eq!(path[3], PathSegment::Key("baz"));
+        assert!(eoi(i).is_complete());
     }
 }
 
 #[test]
-fn test_separated_single_item() {
-    let input_str = "foo";
+fn test_key_path_requires_leading_key() {
+    let iter = StrIter::new("[2].foo");
+    assert!(key_path!(iter).is_fail());
+}
+
+#[test]
+fn test_key_path_malformed_segment() {
+    let iter = StrIter::new("foo.[2]");
+    assert!(key_path!(iter).is_abort());
+}
+
+#[test]
+fn test_quoted_string() {
+    let input_str = "\"foo\\\"bar\"";
     let iter = StrIter::new(input_str);
-    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    let result = quoted_string!(iter, b'"', b'\\');
     assert!(result.is_complete());
-    if let Result::Complete(_, list) = result {
-        assert_eq!(list.len(), 1);
-        assert_eq!(list[0], "foo");
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "foo\"bar");
+        assert!(eoi(i).is_complete());
     }
 }
 
 #[test]
-fn test_separated_empty_list() {
-    let input_str = "";
+fn test_quoted_string_no_quote() {
+    let iter = StrIter::new("foo\"");
+    assert!(quoted_string!(iter, b'"', b'\\').is_fail());
+}
+
+#[test]
+fn test_quoted_string_unterminated() {
+    let iter = StrIter::new("\"foo\\\"");
+    let result = quoted_string!(iter, b'"', b'\\');
+    assert!(result.is_abort());
+    if let Result::Abort(e) = result {
+        assert_eq!(e.get_msg(), "Unterminated string");
+    }
+}
+
+#[test]
+fn test_escaped() {
+    let input_str = "ab\\ncd\"";
     let iter = StrIter::new(input_str);
-    let result = separated!(iter, text_token!(","), text_token!("foo"));
-    assert!(result.is_fail());
+    let result = escaped!(
+        iter,
+        ascii_alphanumeric,
+        b'\\',
+        satisfy!(|b: &u8| b"n\"\\".contains(b))
+    );
+    assert!(result.is_complete());
+    if let Result::Complete(i, o) = result {
+        assert_eq!(o, "ab\\ncd");
+        assert_eq!(i.get_offset(), 6);
+        assert!(text_token!(i, "\"").is_complete());
+    }
 }
 
 #[test]
-fn test_separated_bad() {
-    let input_str = "bar foo,foo";
+fn test_escaped_bad_escape() {
+    let input_str = "ab\\xcd";
     let iter = StrIter::new(input_str);
-    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    let result = escaped!(
+        iter,
+        ascii_alphanumeric,
+        b'\\',
+        satisfy!(|b: &u8| b"n\"\\".contains(b))
+    );
     assert!(result.is_fail());
 }
 
 #[test]
-fn test_separated_trailing_comma() {
-    let input_str = "foo,foo,foo,";
+fn test_skip_rule() {
+    let input_str = "  // c\n /* b */ x";
     let iter = StrIter::new(input_str);
-    let result = separated!(iter, text_token!(","), text_token!("foo"));
+    let result = skip_rule!(iter, line: "//", block: ("/*", "*/"));
     assert!(result.is_complete());
-    if let Result::Complete(i, list) = result {
-        assert_eq!(list.len(), 3);
-        assert_eq!(list[0], "foo");
-        assert_eq!(list[1], "foo");
-        assert_eq!(list[2], "foo");
-        assert!(text_token!(i, ",").is_complete());
+    if let Result::Complete(i, _) = result {
+        assert_eq!(i.get_offset(), 16);
+        assert!(text_token!(i, "x").is_complete());
     }
 }
 
 #[test]
-fn test_ascii_alphanumeric() {
-    let input_str = "a1";
+fn test_skip_rule_nothing_to_skip() {
+    let input_str = "x";
     let iter = StrIter::new(input_str);
-    let result = repeat!(iter, ascii_alphanumeric);
+    let result = skip_rule!(iter, line: "//");
     assert!(result.is_complete());
-    if let Result::Complete(i, list) = result {
-        assert_eq!(list.len(), 2);
-        assert_eq!(list[0], b'a');
-        assert_eq!(list[1], b'1');
-        assert!(eoi(i).is_complete());
+    if let Result::Complete(i, _) = result {
+        assert_eq!(i.get_offset(), 0);
     }
 }
 
 #[test]
-fn test_ascii_alphanumeric_fail() {
-    let input_str = "-";
+fn test_skip_rule_unterminated_block() {
+    let input_str = " /* b ";
     let iter = StrIter::new(input_str);
-    let result = ascii_alphanumeric(iter);
-    assert!(result.is_fail());
+    let result = skip_rule!(iter, block: ("/*", "*/"));
+    assert!(result.is_abort());
+}
+
+#[derive(Debug, PartialEq)]
+enum Record {
+    Byte(u8),
+    Triple(String),
 }
 
 #[test]
-fn test_ascii_digit() {
-    let input_str = "12";
-    let iter = StrIter::new(input_str);
-    let result = repeat!(iter, ascii_digit);
+fn test_tagged() {
+    let input = vec![0x01, b'a', 0x02, b'b', b'c', b'd'];
+    let iter = SliceIter::new(&input);
+    let result = tagged!(iter, parse_byte, {
+        0x01 => Record::Byte(parse_byte),
+        0x02 => Record::Triple(parse_three),
+    });
     assert!(result.is_complete());
-    if let Result::Complete(i, list) = result {
-        assert_eq!(list.len(), 2);
-        assert_eq!(list[0], b'1');
-        assert_eq!(list[1], b'2');
-        assert!(eoi(i).is_complete());
+    if let Result::Complete(i, record) = result {
+        assert_eq!(record, Record::Byte(b'a'));
+        assert_eq!(i.get_offset(), 2);
+        let result = tagged!(i, parse_byte, {
+            0x01 => Record::Byte(parse_byte),
+            0x02 => Record::Triple(parse_three),
+        });
+        assert!(result.is_complete());
+        if let Result::Complete(_, record) = result {
+            assert_eq!(record, Record::Triple("bcd".to_string()));
+        }
     }
 }
 
 #[test]
-fn test_ascii_digit_fail() {
-    let input_str = "-";
-    let iter = StrIter::new(input_str);
-    let result = ascii_digit(iter);
+fn test_tagged_unknown_tag() {
+    let input = vec![0x03, b'a'];
+    let iter = SliceIter::new(&input);
+    let result = tagged!(iter, parse_byte, {
+        0x01 => Record::Byte(parse_byte),
+        0x02 => Record::Triple(parse_three),
+    });
+    assert!(result.is_abort());
+}
+
+#[test]
+fn test_furthest_tracker() {
+    let iter = FurthestTracker::new(StrIter::new("foo"));
+    let mut cloned = iter.clone();
+    cloned.next();
+    cloned.next();
+    assert_eq!(iter.get_offset(), 0);
+    assert_eq!(iter.furthest_offset(), 1);
+    iter.reset_furthest();
+    assert_eq!(iter.furthest_offset(), 0);
+}
+
+#[test]
+fn test_track_furthest() {
+    let input_str = "ab!";
+    let iter = FurthestTracker::new(StrIter::new(input_str));
+    let result = track_furthest!(
+        iter,
+        either!(
+            do_each!(
+                _ => ascii_alpha,
+                _ => ascii_alpha,
+                _ => ascii_digit,
+                ()
+            ),
+            do_each!(
+                _ => ascii_digit,
+                ()
+            )
+        )
+    );
     assert!(result.is_fail());
+    if let Result::Fail(e) = result {
+        assert_eq!(e.get_offset(), 2);
+        assert_eq!(e.get_cause().unwrap().get_offset(), 1);
+    }
 }
 
 #[test]
-fn test_ascii_alpha() {
+fn test_track_furthest_complete() {
     let input_str = "ab";
-    let iter = StrIter::new(input_str);
-    let result = repeat!(iter, ascii_alpha);
+    let iter = FurthestTracker::new(StrIter::new(input_str));
+    let result = track_furthest!(iter, text_token!("ab"));
     assert!(result.is_complete());
-    if let Result::Complete(i, list) = result {
-        assert_eq!(list.len(), 2);
-        assert_eq!(list[0], b'a');
-        assert_eq!(list[1], b'b');
-        assert!(eoi(i).is_complete());
-    }
 }
 
 #[test]
-fn test_ascii_alpha_fail() {
-    let input_str = "1";
+fn test_separated_iter() {
+    let input_str = "a,b,c";
     let iter = StrIter::new(input_str);
-    let result = ascii_alpha(iter);
-    assert!(result.is_fail());
+    let items: Vec<_> = separated_iter(iter, |i| text_token!(i, ","), ascii_alpha).collect();
+    assert_eq!(items.len(), 3);
+    assert_eq!(*items[0].as_ref().unwrap(), b'a');
+    assert_eq!(*items[1].as_ref().unwrap(), b'b');
+    assert_eq!(*items[2].as_ref().unwrap(), b'c');
 }
 
 #[test]
-fn test_consume_all() {
-    let input_str = "foo;";
+fn test_separated_iter_missing_item() {
+    let input_str = "a,1";
     let iter = StrIter::new(input_str);
-    let result = consume_all!(iter, ascii_alpha);
-    assert!(result.is_complete());
-    if let Result::Complete(i, o) = result {
-        assert_eq!(i.get_offset(), 3);
-        assert_eq!(o, "foo");
-        assert!(text_token!(i, ";").is_complete());
-    }
+    let mut items = separated_iter(iter, |i| text_token!(i, ","), ascii_alpha);
+    assert!(items.next().unwrap().is_ok());
+    assert!(items.next().unwrap().is_err());
+    assert!(items.next().is_none());
+}
+
+fn foo_record(i: StrIter<'_>) -> Result<StrIter<'_>, &str> {
+    do_each!(i,
+        name => text_token!("foo"),
+        _ => text_token!(","),
+        (name)
+    )
+}
+
+#[test]
+fn test_parse_iter() {
+    let mut records = ParseIter::new(StrIter::new("foo,foo,foo,"), foo_record);
+    assert_eq!(records.next().unwrap().unwrap(), "foo");
+    assert_eq!(records.input().unwrap().get_offset(), 4);
+    assert_eq!(records.by_ref().count(), 2);
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_parse_iter_stops_on_error() {
+    let mut records = ParseIter::new(StrIter::new("foo,bar,foo,"), foo_record);
+    assert!(records.next().unwrap().is_ok());
+    assert!(records.next().unwrap().is_err());
+    assert!(records.next().is_none());
+}
+
+fn line_record(i: StrIter<'_>) -> Result<StrIter<'_>, &str> {
+    take_line!(i, require_newline)
+}
+
+#[test]
+fn test_parse_iter_trailing_partial_record() {
+    let mut records = ParseIter::new(StrIter::new("one\ntwo"), line_record);
+    assert_eq!(records.next().unwrap().unwrap(), "one");
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(err.get_context().get_offset(), 7);
+    assert!(records.next().is_none());
+}
+
+fn statement<'a>(i: StrIter<'a>) -> Result<StrIter<'a>, &'a str> {
+    do_each!(i,
+        name => consume_all!(ascii_alpha),
+        _ => must!(text_token!(";")),
+        (name)
+    )
+}
+
+#[test]
+fn test_parse_all_recovering() {
+    let input_str = "foo;b4r;baz;";
+    let iter = StrIter::new(input_str);
+    let (items, errors) = parse_all_recovering(iter, statement, |i| text_token!(i, ";"));
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0], ("foo", SourceSpan { start: 0, end: 4 }));
+    assert_eq!(items[1], ("baz", SourceSpan { start: 8, end: 12 }));
+    assert_eq!(items[1].1.end, input_str.len());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].get_offset(), 5);
 }
@@ -0,0 +1,71 @@
+// Copyright 2017 Jeremy Wall <jeremy@marzhillstudios.com>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Contains the support for tracing parsers with the `trace_parser!` combinator.
+//!
+//! Tracing only happens when the `trace` cargo feature is enabled. Otherwise
+//! `trace_result` does nothing and the rest of this module is compiled out.
+//! Trace lines are written to stderr unless a sink has been installed with
+//! `set_trace_sink`.
+#[cfg(feature = "trace")]
+use std::sync::Mutex;
+
+use super::{InputIter, Result};
+
+#[cfg(feature = "trace")]
+static TRACE_SINK: Mutex<Option<fn(&str)>> = Mutex::new(None);
+
+/// Installs a sink that receives every trace line instead of stderr.
+#[cfg(feature = "trace")]
+pub fn set_trace_sink(f: fn(&str)) {
+    *TRACE_SINK.lock().unwrap_or_else(|e| e.into_inner()) = Some(f);
+}
+
+/// Removes any installed sink so trace lines go back to stderr.
+#[cfg(feature = "trace")]
+pub fn clear_trace_sink() {
+    *TRACE_SINK.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Sends a single trace line to the installed sink or stderr.
+#[cfg(feature = "trace")]
+pub fn emit(line: &str) {
+    let sink = *TRACE_SINK.lock().unwrap_or_else(|e| e.into_inner());
+    match sink {
+        Some(f) => f(line),
+        None => eprintln!("{}", line),
+    }
+}
+
+/// Returns the name of the Result variant for use in traces.
+#[cfg(feature = "trace")]
+pub fn variant_name<I: InputIter, O>(result: &Result<I, O>) -> &'static str {
+    match result {
+        Result::Complete(_, _) => "Complete",
+        Result::Incomplete(_) => "Incomplete",
+        Result::Fail(_) => "Fail",
+        Result::Abort(_) => "Abort",
+    }
+}
+
+/// Records a trace line for a labeled sub parser that started at offset.
+#[cfg(feature = "trace")]
+pub fn trace_result<I: InputIter, O>(label: &str, offset: usize, result: &Result<I, O>) {
+    emit(&format!("{} @{}: {}", label, offset, variant_name(result)));
+}
+
+/// Records a trace line for a labeled sub parser that started at offset.
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub fn trace_result<I: InputIter, O>(_label: &str, _offset: usize, _result: &Result<I, O>) {}